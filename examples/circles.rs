@@ -3,6 +3,7 @@ use std::sync::Arc;
 use glam::{Vec2, Vec4, IVec2, Mat4, Vec3};
 use hope::gl::enums::glBlendFunc::GLBlendFunc;
 use hope::gl::enums::glBufferBit::GLBufferBit;
+use hope::gl::enums::glPrimitive::GLPrimitive;
 use hope::gl::enums::glCompareFunc::GLCompareFunc;
 use hope::gl::enums::glFunction::GLFunction;
 use hope::gl::enums::glStencilOp::GLStencilOp;
@@ -11,6 +12,7 @@ use hope::gl::glContext::GLContext;
 use hope::gl::glTexture::GLTexture;
 use hope::gl::sampler::sampler2d::Sampler2D;
 use hope::gl::shader::program::Program;
+use hope::gl::shader::fragment::FragmentOutput;
 use hope::gl::util::read_image;
 use image::ColorType;
 use shader::Shader;
@@ -38,6 +40,8 @@ struct CircleShader
 
 impl Program<CircleVarying> for CircleShader
 {
+    const DISCARD: bool = true;
+
     fn vertex(&mut self, index: usize) -> Vec4
     {
         self.varyings.push(CircleVarying { tex_coords: self.tex_coords[index] });
@@ -53,6 +57,14 @@ impl Program<CircleVarying> for CircleShader
     {
         self.circle.sample(varying.tex_coords);
     }
+
+    //丢弃圆以外透明的部分，这样圆形以外的区域不会写入模板
+    fn fragment_output(&mut self, varying: &CircleVarying, pos: IVec2) -> FragmentOutput
+    {
+        let color = self.fragment(varying, pos);
+
+        if color.a == 0 { FragmentOutput::discard() } else { color.into() }
+    }
 }
 
 #[derive(Default, Clone, Copy, Varying)]
@@ -113,9 +125,6 @@ fn main()
     fb.attach_color();
     fb.attach_stencil();
 
-    gl.enable(GLFunction::AlphaTest);
-    gl.alpha_func(GLCompareFunc::Greater, 0);
-
     gl.enable(GLFunction::StencilTest);
     gl.stencil_op(GLStencilOp::Keep, GLStencilOp::Keep, GLStencilOp::Replace);
     gl.blend_func(GLBlendFunc::SrcAlpha, GLBlendFunc::OneMinusSrcAlpha);
@@ -140,7 +149,7 @@ fn main()
 
     circle_shader.mvp = view * tr1;
 
-    gl.draw_arrays(&mut circle_shader, 6, 0, &mut fb);
+    gl.draw_arrays(&mut circle_shader, GLPrimitive::Triangles, 6, 0, &mut fb);
 
     gl.color_mask(true, true, true, true);
     gl.stencil_func(GLCompareFunc::Equal, 0x01, 0xFF);
@@ -149,7 +158,7 @@ fn main()
     pure_color_shader.mvp = view * tr1;
     pure_color_shader.color = Vec4::new(1.0, 0.0, 0.0, 0.9);
 
-    gl.draw_arrays(&mut pure_color_shader, 6, 0, &mut fb);
+    gl.draw_arrays(&mut pure_color_shader, GLPrimitive::Triangles, 6, 0, &mut fb);
 
     //第二个圆
     gl.color_mask(false, false, false, false);
@@ -159,7 +168,7 @@ fn main()
 
     circle_shader.mvp = view * tr2;
 
-    gl.draw_arrays(&mut circle_shader, 6, 0, &mut fb);
+    gl.draw_arrays(&mut circle_shader, GLPrimitive::Triangles, 6, 0, &mut fb);
 
     gl.color_mask(true, true, true, true);
     gl.stencil_func(GLCompareFunc::Equal, 0x01, 0xFF);
@@ -168,7 +177,7 @@ fn main()
     pure_color_shader.mvp = view * tr2;
     pure_color_shader.color = Vec4::new(0.0, 1.0, 0.0, 0.7);
 
-    gl.draw_arrays(&mut pure_color_shader, 6, 0, &mut fb);
+    gl.draw_arrays(&mut pure_color_shader, GLPrimitive::Triangles, 6, 0, &mut fb);
 
     //第三个圆
     gl.color_mask(false, false, false, false);
@@ -178,7 +187,7 @@ fn main()
 
     circle_shader.mvp = view * tr3;
 
-    gl.draw_arrays(&mut circle_shader, 6, 0, &mut fb);
+    gl.draw_arrays(&mut circle_shader, GLPrimitive::Triangles, 6, 0, &mut fb);
 
     gl.color_mask(true, true, true, true);
     gl.stencil_func(GLCompareFunc::Equal, 0x01, 0xFF);
@@ -186,7 +195,7 @@ fn main()
 
     pure_color_shader.mvp = view * tr3;
     pure_color_shader.color = Vec4::new(0.0, 0.0, 1.0, 0.7);
-    gl.draw_arrays(&mut pure_color_shader, 6, 0, &mut fb);
+    gl.draw_arrays(&mut pure_color_shader, GLPrimitive::Triangles, 6, 0, &mut fb);
 
     image::save_buffer("./circles.png", fb.get_color_buffer(), 1920, 1080, ColorType::Rgba8).unwrap()
 }
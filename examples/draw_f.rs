@@ -1,5 +1,6 @@
 use glam::{Vec4, IVec2, Mat4, EulerRot, Vec3};
 use hope::gl::enums::glBufferBit::GLBufferBit;
+use hope::gl::enums::glPrimitive::GLPrimitive;
 use hope::gl::enums::glCompareFunc::GLCompareFunc;
 use hope::gl::enums::glFunction::GLFunction;
 use hope::gl::glColor::GLColor;
@@ -81,7 +82,7 @@ fn main()
     gl.depth_func(GLCompareFunc::Greater);
     gl.clear(GLBufferBit::Color | GLBufferBit::Depth, &mut fb);
 
-    gl.draw_arrays(&mut shader, 6 * 16, 0, &mut fb);
+    gl.draw_arrays(&mut shader, GLPrimitive::Triangles, 6 * 16, 0, &mut fb);
 
     image::save_buffer("./f.png", fb.get_color_buffer(), 1280, 720, ColorType::Rgba8).unwrap()
 }
@@ -0,0 +1,132 @@
+use glam::{Vec4, IVec2, Mat4, Vec3, EulerRot};
+use hope::gl::enums::glBufferBit::GLBufferBit;
+use hope::gl::enums::glFunction::GLFunction;
+use hope::gl::enums::glPrimitive::GLPrimitive;
+use hope::gl::glColor::GLColor;
+use hope::gl::shader::fragment::FragmentOutput;
+use hope::gl::{glContext::GLContext, shader::program::Program};
+use hope::gl::shader::shader::Shader;
+use hope::make_color;
+use image::ColorType;
+use shader::Shader;
+use varying::Varying;
+use hope::gl::shader::varying::Varying;
+
+//一次绘制同时输出G-buffer的颜色和法线
+#[derive(Default, Shader)]
+struct GBufferShader
+{
+    #[varying(GBufferVarying)]
+    varyings: Vec<GBufferVarying>,
+
+    positions: Vec<Vec3>,
+    normals: Vec<Vec3>,
+    colors: Vec<Vec4>,
+
+    #[uniform]
+    mvp: Mat4,
+
+    #[uniform]
+    model: Mat4,
+}
+
+impl Program<GBufferVarying> for GBufferShader
+{
+    fn vertex(&mut self, index: usize) -> Vec4
+    {
+        self.varyings.push(GBufferVarying {
+            color: self.colors[index],
+            normal: self.model.transform_vector3(self.normals[index]),
+        });
+
+        self.mvp * Vec4::from((self.positions[index], 1.0))
+    }
+
+    fn fragment(&mut self, varying: &GBufferVarying, _: IVec2) -> GLColor
+    {
+        varying.color.into()
+    }
+
+    fn sample(&mut self, _: &GBufferVarying)
+    {
+
+    }
+
+    //第0个颜色附着是颜色，第1个是映射到[0, 1]的法线
+    fn fragment_output(&mut self, varying: &GBufferVarying, pos: IVec2) -> FragmentOutput
+    {
+        let normal = varying.normal.normalize() * 0.5 + 0.5;
+
+        FragmentOutput::new(self.fragment(varying, pos))
+            .with_color(1, Vec4::from((normal, 1.0)))
+    }
+}
+
+#[derive(Clone, Copy, Default, Varying)]
+struct GBufferVarying
+{
+    color: Vec4,
+    normal: Vec3,
+}
+
+fn main()
+{
+    let mut gl = GLContext::new(1280, 720);
+    let mut fb = gl.create_buffer();
+    let mut shader = GBufferShader::default();
+
+    //两个颜色附着和一个深度附着
+    fb.attach_color_at(0);
+    fb.attach_color_at(1);
+    fb.attach_depth();
+
+    gl.enable(GLFunction::CullFace);
+    gl.enable(GLFunction::DepthTest);
+
+    (shader.positions, shader.normals, shader.colors) = make_cube();
+
+    let proj = Mat4::perspective_rh(60f32.to_radians(), 1280. / 720., 1., 100.);
+    let view = Mat4::look_at_rh(Vec3::new(0., 0., 4.), Vec3::ZERO, Vec3::NEG_Y);
+    shader.model = Mat4::from_euler(EulerRot::YXZ, 35f32.to_radians(), 25f32.to_radians(), 0.);
+    shader.mvp = proj * view * shader.model;
+
+    gl.clear_color(make_color!(0));
+    gl.clear(GLBufferBit::Color | GLBufferBit::Depth, &mut fb);
+
+    gl.draw_arrays(&mut shader, GLPrimitive::Triangles, 6 * 6, 0, &mut fb);
+
+    image::save_buffer("./mrt_color.png", fb.get_color_buffer_at(0), 1280, 720, ColorType::Rgba8).unwrap();
+    image::save_buffer("./mrt_normal.png", fb.get_color_buffer_at(1), 1280, 720, ColorType::Rgba8).unwrap()
+}
+
+fn make_cube() -> (Vec<Vec3>, Vec<Vec3>, Vec<Vec4>)
+{
+    let faces = [
+        (Vec3::X, Vec3::Y, make_color!(200, 70, 120)),
+        (Vec3::NEG_X, Vec3::Y, make_color!(80, 70, 200)),
+        (Vec3::Y, Vec3::Z, make_color!(70, 200, 210)),
+        (Vec3::NEG_Y, Vec3::Z, make_color!(200, 200, 70)),
+        (Vec3::Z, Vec3::X, make_color!(210, 100, 70)),
+        (Vec3::NEG_Z, Vec3::X, make_color!(76, 210, 100)),
+    ];
+
+    let mut positions = Vec::with_capacity(36);
+    let mut normals = Vec::with_capacity(36);
+    let mut colors = Vec::with_capacity(36);
+
+    for (normal, up, color) in faces
+    {
+        //right和up组成的四边形从外面看是逆时针的
+        let right = up.cross(normal);
+        let corners = [normal - right - up, normal + right - up, normal + right + up, normal - right + up];
+
+        for i in [0, 1, 2, 0, 2, 3]
+        {
+            positions.push(corners[i]);
+            normals.push(normal);
+            colors.push(color.into());
+        }
+    }
+
+    (positions, normals, colors)
+}
\ No newline at end of file
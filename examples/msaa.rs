@@ -0,0 +1,98 @@
+use std::f32::consts::PI;
+
+use glam::{Vec4, IVec2, Vec3};
+use hope::gl::enums::glBufferBit::GLBufferBit;
+use hope::gl::enums::glFunction::GLFunction;
+use hope::gl::enums::glPrimitive::GLPrimitive;
+use hope::gl::enums::glSamplePoint::GLSamplePoint;
+use hope::gl::glColor::GLColor;
+use hope::gl::{glContext::GLContext, shader::program::Program};
+use hope::gl::shader::shader::Shader;
+use hope::make_color;
+use image::ColorType;
+use shader::Shader;
+use varying::Varying;
+use hope::gl::shader::varying::Varying;
+
+#[derive(Default, Shader)]
+struct ColorShader
+{
+    #[varying(ColorVarying)]
+    varyings: Vec<ColorVarying>,
+
+    positions: Vec<Vec3>,
+    colors: Vec<Vec4>,
+}
+
+impl Program<ColorVarying> for ColorShader
+{
+    fn vertex(&mut self, index: usize) -> Vec4
+    {
+        self.varyings.push(ColorVarying { color: self.colors[index] });
+        Vec4::from((self.positions[index], 1.0))
+    }
+
+    fn fragment(&mut self, varying: &ColorVarying, _: IVec2) -> GLColor
+    {
+        varying.color.into()
+    }
+
+    fn sample(&mut self, _: &ColorVarying)
+    {
+
+    }
+}
+
+#[derive(Clone, Copy, Default, Varying)]
+struct ColorVarying
+{
+    color: Vec4,
+}
+
+fn main()
+{
+    let mut gl = GLContext::new(1280, 720);
+    let mut shader = ColorShader::default();
+
+    //4x多重采样的帧缓冲，深度测试对每个采样点单独进行
+    let mut msaa = gl.create_multisample_buffer(GLSamplePoint::X4);
+    msaa.attach_color();
+    msaa.attach_depth();
+
+    //左边：细长的三角形扇
+    for i in 0..24
+    {
+        let a0 = i as f32 / 24. * PI * 2.;
+        let a1 = a0 + PI / 48.;
+
+        shader.positions.push(Vec3::new(-0.5, 0., 0.));
+        shader.positions.push(Vec3::new(-0.5 + a0.cos() * 0.4, a0.sin() * 0.7, 0.));
+        shader.positions.push(Vec3::new(-0.5 + a1.cos() * 0.4, a1.sin() * 0.7, 0.));
+
+        let color = Vec4::new(i as f32 / 24., 0.3, 1. - i as f32 / 24., 1.);
+        shader.colors.extend([color; 3]);
+    }
+
+    //右边：两个互相穿插的三角形，交线也是平滑的
+    shader.positions.extend([
+        Vec3::new(0.1, 0.6, -0.5), Vec3::new(0.1, -0.6, -0.5), Vec3::new(0.9, 0., 0.5),
+        Vec3::new(0.9, 0.6, -0.5), Vec3::new(0.1, 0., 0.5), Vec3::new(0.9, -0.6, -0.5),
+    ]);
+
+    shader.colors.extend([Vec4::new(0.9, 0.3, 0.2, 1.); 3]);
+    shader.colors.extend([Vec4::new(0.2, 0.7, 0.3, 1.); 3]);
+
+    gl.enable(GLFunction::DepthTest);
+
+    gl.clear_color(make_color!(255));
+    gl.clear(GLBufferBit::Color | GLBufferBit::Depth, &mut msaa);
+
+    gl.draw_arrays(&mut shader, GLPrimitive::Triangles, 24 * 3 + 6, 0, &mut msaa);
+
+    //把所有采样点平均之后输出到普通的帧缓冲
+    let mut fb = gl.create_buffer();
+    fb.attach_color();
+    msaa.resolve(&mut fb);
+
+    image::save_buffer("./msaa.png", fb.get_color_buffer(), 1280, 720, ColorType::Rgba8).unwrap()
+}
\ No newline at end of file
@@ -2,6 +2,7 @@ use std::sync::Arc;
 
 use glam::{Vec4, IVec2, Mat4, EulerRot, Vec3, Vec2};
 use hope::gl::enums::glBufferBit::GLBufferBit;
+use hope::gl::enums::glPrimitive::GLPrimitive;
 use hope::gl::enums::glSamplePoint::GLSamplePoint;
 use hope::gl::glColor::GLColor;
 use hope::gl::glTexture::GLTexture;
@@ -92,7 +93,7 @@ fn main()
     gl.clear_color(make_color!(255));
     gl.clear(GLBufferBit::Color, &mut fb);
 
-    gl.draw_arrays(&mut shader, 6, 0, &mut fb);
+    gl.draw_arrays(&mut shader, GLPrimitive::Triangles, 6, 0, &mut fb);
 
     image::save_buffer("./plane.png", fb.get_color_buffer(), 1920, 1080, ColorType::Rgba8).unwrap()
 }
@@ -0,0 +1,181 @@
+use std::f32::consts::PI;
+
+use glam::{Vec4, IVec2, Vec2};
+use hope::gl::enums::glBlendFunc::GLBlendFunc;
+use hope::gl::enums::glBufferBit::GLBufferBit;
+use hope::gl::enums::glFunction::GLFunction;
+use hope::gl::enums::glPrimitive::GLPrimitive;
+use hope::gl::glColor::GLColor;
+use hope::gl::{glContext::GLContext, shader::program::Program};
+use hope::gl::shader::shader::Shader;
+use hope::make_color;
+use image::ColorType;
+use shader::Shader;
+use varying::Varying;
+use hope::gl::shader::varying::Varying;
+
+#[derive(Default, Shader)]
+struct ColorShader
+{
+    #[varying(ColorVarying)]
+    varyings: Vec<ColorVarying>,
+
+    positions: Vec<Vec2>,
+    colors: Vec<Vec4>,
+
+    //点精灵内的坐标，sprite为true时用来做径向渐变
+    sprite: bool,
+    coord: Vec2,
+}
+
+impl Program<ColorVarying> for ColorShader
+{
+    fn vertex(&mut self, index: usize) -> Vec4
+    {
+        self.varyings.push(ColorVarying { color: self.colors[index] });
+        Vec4::from((self.positions[index], 0.0, 1.0))
+    }
+
+    fn fragment(&mut self, varying: &ColorVarying, _: IVec2) -> GLColor
+    {
+        if self.sprite
+        {
+            let falloff = (1. - self.coord.distance(Vec2::splat(0.5)) * 2.).max(0.);
+            (varying.color * Vec4::new(1., 1., 1., falloff)).into()
+        }
+        else
+        {
+            varying.color.into()
+        }
+    }
+
+    fn sample(&mut self, _: &ColorVarying)
+    {
+
+    }
+
+    fn point_coord(&mut self, coord: Vec2)
+    {
+        self.coord = coord;
+    }
+}
+
+#[derive(Clone, Copy, Default, Varying)]
+struct ColorVarying
+{
+    color: Vec4,
+}
+
+fn rainbow(t: f32) -> Vec4
+{
+    Vec4::new((t * PI * 2.).cos() * 0.5 + 0.5, (t * PI * 2. + 2.).cos() * 0.5 + 0.5, (t * PI * 2. + 4.).cos() * 0.5 + 0.5, 1.)
+}
+
+fn main()
+{
+    let mut gl = GLContext::new(1280, 720);
+    let mut fb = gl.create_buffer();
+    let mut shader = ColorShader::default();
+
+    fb.attach_color();
+
+    //三角形带和三角形扇的绕序都是逆时针，开启面剔除后也应该完整地画出来
+    gl.enable(GLFunction::CullFace);
+
+    gl.clear_color(make_color!(255));
+    gl.clear(GLBufferBit::Color, &mut fb);
+
+    //左上：三角形带
+    for i in 0..12
+    {
+        let x = -0.9 + i as f32 / 11. * 0.8;
+        let y = if i % 2 == 0 { 0.8 } else { 0.2 };
+
+        shader.positions.push(Vec2::new(x, y));
+        shader.colors.push(rainbow(i as f32 / 12.));
+    }
+
+    gl.draw_arrays(&mut shader, GLPrimitive::TriangleStrip, 12, 0, &mut fb);
+
+    //右上：三角形扇
+    shader.positions = vec![Vec2::new(0.5, 0.5)];
+    shader.colors = vec![Vec4::ONE];
+
+    for i in 0..=16
+    {
+        let angle = i as f32 / 16. * PI * 2.;
+
+        shader.positions.push(Vec2::new(0.5 + angle.cos() * 0.2, 0.5 + angle.sin() * 0.35));
+        shader.colors.push(rainbow(i as f32 / 16.));
+    }
+
+    gl.draw_arrays(&mut shader, GLPrimitive::TriangleFan, 18, 0, &mut fb);
+
+    //左下：线环和线段
+    shader.positions.clear();
+    shader.colors.clear();
+
+    for i in 0..16
+    {
+        let angle = i as f32 / 16. * PI * 2.;
+
+        shader.positions.push(Vec2::new(-0.5 + angle.cos() * 0.3, -0.5 + angle.sin() * 0.4));
+        shader.colors.push(rainbow(i as f32 / 16.));
+    }
+
+    //抗锯齿的宽线，覆盖率保存在alpha里，需要配合混合使用
+    gl.enable(GLFunction::Blend);
+    gl.blend_func(GLBlendFunc::SrcAlpha, GLBlendFunc::OneMinusSrcAlpha);
+    gl.enable(GLFunction::LineSmooth);
+    gl.line_width(4.);
+
+    gl.draw_arrays(&mut shader, GLPrimitive::LineLoop, 16, 0, &mut fb);
+
+    gl.disable(GLFunction::LineSmooth);
+    gl.disable(GLFunction::Blend);
+    gl.line_width(1.);
+
+    for i in 0..16
+    {
+        shader.positions.insert(i * 2, Vec2::new(-0.5, -0.5));
+        shader.colors.insert(i * 2, Vec4::new(0., 0., 0., 1.));
+    }
+
+    gl.draw_arrays(&mut shader, GLPrimitive::Lines, 32, 0, &mut fb);
+
+    //右下：点，上半部分是1像素的点，下半部分是带径向渐变的圆形点精灵
+    shader.positions.clear();
+    shader.colors.clear();
+
+    for i in 0..32
+    {
+        for j in 0..16
+        {
+            shader.positions.push(Vec2::new(0.2 + i as f32 / 31. * 0.6, -0.5 + j as f32 / 15. * 0.3));
+            shader.colors.push(rainbow((i + j) as f32 / 46.));
+        }
+    }
+
+    gl.draw_arrays(&mut shader, GLPrimitive::Points, 32 * 16, 0, &mut fb);
+
+    shader.positions.clear();
+    shader.colors.clear();
+
+    for i in 0..8
+    {
+        for j in 0..3
+        {
+            shader.positions.push(Vec2::new(0.25 + i as f32 / 7. * 0.5, -0.85 + j as f32 / 2. * 0.25));
+            shader.colors.push(rainbow((i + j) as f32 / 10.));
+        }
+    }
+
+    gl.enable(GLFunction::Blend);
+    gl.enable(GLFunction::PointSmooth);
+    gl.point_size(32.);
+    shader.sprite = true;
+
+    gl.draw_arrays(&mut shader, GLPrimitive::Points, 8 * 3, 0, &mut fb);
+
+    image::save_buffer("./primitives.png", fb.get_color_buffer(), 1280, 720, ColorType::Rgba8).unwrap()
+}
\ No newline at end of file
@@ -0,0 +1,171 @@
+use std::sync::Arc;
+
+use glam::{Vec4, IVec2, Mat4, EulerRot, Vec3, Vec2};
+use hope::gl::enums::glBufferBit::GLBufferBit;
+use hope::gl::enums::glPrimitive::GLPrimitive;
+use hope::gl::glColor::GLColor;
+use hope::gl::glFrameBuffer::GLFrameBuffer;
+use hope::gl::sampler::GLFilterFunc;
+use hope::gl::sampler::sampler2d::Sampler2D;
+use hope::gl::{glContext::GLContext, shader::program::Program};
+use hope::gl::shader::shader::Shader;
+use hope::make_color;
+use image::ColorType;
+use shader::Shader;
+use varying::Varying;
+use hope::gl::shader::varying::Varying;
+use hope::gl::sampler::sampler::Sampler;
+
+//第一遍绘制彩色三角形
+#[derive(Default, Shader)]
+struct TriangleShader
+{
+    #[varying(ColorVarying)]
+    varyings: Vec<ColorVarying>,
+
+    positions: Vec<Vec2>,
+    colors: Vec<Vec4>,
+}
+
+impl Program<ColorVarying> for TriangleShader
+{
+    fn vertex(&mut self, index: usize) -> Vec4
+    {
+        self.varyings.push(ColorVarying { color: self.colors[index] });
+        Vec4::from((self.positions[index], 0.0, 1.0))
+    }
+
+    fn fragment(&mut self, varying: &ColorVarying, _: IVec2) -> GLColor
+    {
+        varying.color.into()
+    }
+
+    fn sample(&mut self, _: &ColorVarying)
+    {
+
+    }
+}
+
+#[derive(Clone, Copy, Default, Varying)]
+struct ColorVarying
+{
+    color: Vec4,
+}
+
+//第二遍把第一遍的结果当作纹理贴到平面上
+#[derive(Default, Shader)]
+struct PlaneShader
+{
+    #[varying(TextureVarying)]
+    varyings: Vec<TextureVarying>,
+
+    positions: Vec<Vec4>,
+    tex_coords: Vec<Vec2>,
+
+    #[uniform]
+    mvp: Mat4,
+
+    #[sampler]
+    scene: Sampler2D,
+}
+
+impl Program<TextureVarying> for PlaneShader
+{
+    fn vertex(&mut self, index: usize) -> Vec4
+    {
+        self.varyings.push(TextureVarying { tex_coords: self.tex_coords[index] });
+        self.mvp * self.positions[index]
+    }
+
+    fn fragment(&mut self, _: &TextureVarying, _: IVec2) -> GLColor
+    {
+        self.scene.get_color()
+    }
+
+    fn sample(&mut self, varying: &TextureVarying)
+    {
+        self.scene.sample(varying.tex_coords);
+    }
+}
+
+#[derive(Clone, Copy, Default, Varying)]
+struct TextureVarying
+{
+    tex_coords: Vec2,
+}
+
+fn main()
+{
+    let mut gl = GLContext::new(1280, 720);
+
+    //离屏帧缓冲，视口要和它的大小一致
+    let mut offscreen = GLFrameBuffer::new(512, 512);
+    let mut triangle_shader = TriangleShader::default();
+
+    offscreen.attach_color();
+
+    triangle_shader.positions = vec![Vec2::new(0.0, 0.8), Vec2::new(-0.8, -0.8), Vec2::new(0.8, -0.8)];
+    triangle_shader.colors = vec![
+        Vec4::new(1.0, 0.0, 0.0, 1.0),
+        Vec4::new(0.0, 1.0, 0.0, 1.0),
+        Vec4::new(0.0, 0.0, 1.0, 1.0)
+    ];
+
+    gl.viewport(0, 0, 512, 512);
+    gl.clear_color(make_color!(40, 40, 40));
+    gl.clear(GLBufferBit::Color, &mut offscreen);
+    gl.draw_arrays(&mut triangle_shader, GLPrimitive::Triangles, 3, 0, &mut offscreen);
+
+    //颜色附着直接变成纹理，不会复制
+    let mut scene = offscreen.take_color_texture(0).unwrap();
+    scene.create_mipmap(None);
+
+    gl.viewport(0, 0, 1280, 720);
+    let mut fb = gl.create_buffer();
+    let mut plane_shader = PlaneShader::default();
+
+    fb.attach_color();
+
+    (plane_shader.positions, plane_shader.tex_coords) = make_quad(512.0, 512.0);
+
+    plane_shader.scene = Sampler2D::new(Arc::new(scene));
+    plane_shader.scene.set_mag_filter(GLFilterFunc::Linear);
+    plane_shader.scene.set_min_filter(GLFilterFunc::LinearMipmapLinear);
+
+    let proj = Mat4::perspective_rh(60f32.to_radians(), 1280. / 720., 1., 2000.);
+    let view = Mat4::look_at_rh(Vec3::new(0., 0., -1.), Vec3::new(0., 0., 0.), Vec3::new(0., -1., 0.)).inverse();
+    let model = Mat4::from_translation(Vec3::new(-100.0, -256.0, 600.0)) *
+    Mat4::from_euler(EulerRot::ZXY, 0f32.to_radians(), 0f32.to_radians(), -45f32.to_radians());
+    plane_shader.mvp = proj * view * model;
+
+    gl.clear_color(make_color!(255));
+    gl.clear(GLBufferBit::Color, &mut fb);
+    gl.draw_arrays(&mut plane_shader, GLPrimitive::Triangles, 6, 0, &mut fb);
+
+    image::save_buffer("./render_to_texture.png", fb.get_color_buffer(), 1280, 720, ColorType::Rgba8).unwrap()
+}
+
+fn make_quad(width: f32, height: f32) -> (Vec<Vec4>, Vec<Vec2>)
+{
+    let vertices = vec![
+        Vec4::new(0., 0., 0., 1.),
+        Vec4::new(width, height ,0., 1.),
+        Vec4::new(width, 0., 0., 1.),
+
+        Vec4::new(0., 0., 0., 1.),
+        Vec4::new(0., height, 0., 1.),
+        Vec4::new(width, height ,0., 1.),
+    ];
+
+    let tex_coords = vec![
+        Vec2::new(0.0, 0.0),
+        Vec2::new(1.0, 1.0),
+        Vec2::new(1.0, 0.0),
+
+        Vec2::new(0.0, 0.0),
+        Vec2::new(0.0, 1.0),
+        Vec2::new(1.0, 1.0),
+    ];
+
+    (vertices, tex_coords)
+}
\ No newline at end of file
@@ -1,5 +1,6 @@
 use glam::{Vec4, IVec2, Vec2};
 use hope::gl::enums::glBufferBit::GLBufferBit;
+use hope::gl::enums::glPrimitive::GLPrimitive;
 use hope::gl::glColor::GLColor;
 use hope::gl::{glContext::GLContext, shader::program::Program};
 use hope::gl::shader::shader::Shader;
@@ -76,7 +77,7 @@ fn main()
     gl.clear_color(make_color!(255));
     gl.clear(GLBufferBit::Color, &mut fb);
 
-    gl.draw_arrays(&mut shader, 3, 0, &mut fb);
+    gl.draw_arrays(&mut shader, GLPrimitive::Triangles, 3, 0, &mut fb);
 
     image::save_buffer("./triangle.png", fb.get_color_buffer(), 1280, 720, ColorType::Rgba8).unwrap()
 }
\ No newline at end of file
@@ -15,6 +15,7 @@ pub fn shader(input: TokenStream) -> TokenStream
     let mut attribute_fields = Vec::new();
     let mut sampler_fields = Vec::new();
     let mut clone_fields = Vec::new();
+    let mut all_fields = Vec::new();
 
     if let Data::Struct(ds) = input.data
     {
@@ -22,6 +23,8 @@ pub fn shader(input: TokenStream) -> TokenStream
         {
             fields.named.into_iter().for_each(|f|
             {
+                all_fields.push(f.ident.clone().unwrap());
+
                 f.attrs.iter().for_each(|attr|
                 {
                     let path = attr.path.to_token_stream().to_string();
@@ -75,6 +78,7 @@ pub fn shader(input: TokenStream) -> TokenStream
 
     let varying_field_type = varying_field.as_ref().unwrap().attrs[0].parse_args::<Type>().unwrap();
     let varying_field_name = varying_field.unwrap().ident.unwrap();
+    let vertex_fields: Vec<_> = all_fields.into_iter().filter(|f| *f != varying_field_name).collect();
 
     quote::quote!
     {
@@ -96,6 +100,24 @@ pub fn shader(input: TokenStream) -> TokenStream
                 &self.#varying_field_name
             }
 
+            fn get_varying_mut(&mut self) -> &mut Vec<#varying_field_type>
+            {
+                &mut self.#varying_field_name
+            }
+
+            fn vertex_clone(&self) -> Self
+            {
+                Self {
+                    #(#vertex_fields: self.#vertex_fields.clone(),)*
+                    ..Default::default()
+                }
+            }
+
+            fn next_n(&mut self, n: usize)
+            {
+                #(self.#attribute_fields.forward_n(n);)*
+            }
+
             fn compute_level(&mut self, sample_point: i32)
             {
                 #(self.#sampler_fields.compute_level(sample_point);)*
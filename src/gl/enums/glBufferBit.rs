@@ -1,13 +1,61 @@
-use crate::c_enum_impl;
+#![allow(non_upper_case_globals)]
 
-#[repr(i32)]
-#[derive(Clone, Copy, Default, PartialEq)]
-pub enum GLBufferBit
+/// clear和blit_framebuffer使用的缓冲位，可以用`|`组合
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub struct GLBufferBit(u32);
+
+impl GLBufferBit
+{
+    pub const Color: Self   = Self(0b001);
+    pub const Depth: Self   = Self(0b010);
+    pub const Stencil: Self = Self(0b100);
+
+    /// 是否包含other中的所有位
+    #[inline(always)]
+    pub fn contains(self, other: Self) -> bool
+    {
+        self.0 & other.0 == other.0
+    }
+
+    #[inline(always)]
+    pub fn bits(self) -> u32
+    {
+        self.0
+    }
+}
+
+impl std::ops::BitOr for GLBufferBit
 {
-    #[default]
-    Color   =  0b001,
-    Depth   =  0b010,
-    Stencil =  0b100
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self::Output
+    {
+        Self(self.0 | rhs.0)
+    }
 }
 
-c_enum_impl!(GLBufferBit);
\ No newline at end of file
+impl std::ops::BitOrAssign for GLBufferBit
+{
+    fn bitor_assign(&mut self, rhs: Self)
+    {
+        self.0 |= rhs.0;
+    }
+}
+
+impl std::ops::BitAnd for GLBufferBit
+{
+    type Output = Self;
+
+    fn bitand(self, rhs: Self) -> Self::Output
+    {
+        Self(self.0 & rhs.0)
+    }
+}
+
+impl std::ops::BitAndAssign for GLBufferBit
+{
+    fn bitand_assign(&mut self, rhs: Self)
+    {
+        self.0 &= rhs.0;
+    }
+}
\ No newline at end of file
@@ -0,0 +1,37 @@
+/// 颜色附着的格式，带F的是浮点格式，其它是归一化的定点数(unorm)
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
+pub enum GLColorFormat
+{
+    R8,
+    RG8,
+    #[default]
+    RGBA8,
+    /// 半精度浮点数
+    RGBA16F,
+    R32F,
+    RGBA32F,
+    /// 每个通道都是5位指数的无符号浮点数，适合HDR颜色
+    R11G11B10F,
+}
+
+impl GLColorFormat
+{
+    /// 每个像素的字节数
+    pub fn size(&self) -> usize
+    {
+        match self
+        {
+            GLColorFormat::R8 => 1,
+            GLColorFormat::RG8 => 2,
+            GLColorFormat::RGBA8 | GLColorFormat::R32F | GLColorFormat::R11G11B10F => 4,
+            GLColorFormat::RGBA16F => 8,
+            GLColorFormat::RGBA32F => 16,
+        }
+    }
+
+    /// 浮点格式在写入和混合时不会被限制在[0, 1]内
+    pub fn is_float(&self) -> bool
+    {
+        !matches!(self, GLColorFormat::R8 | GLColorFormat::RG8 | GLColorFormat::RGBA8)
+    }
+}
\ No newline at end of file
@@ -0,0 +1,31 @@
+/// 深度附着的格式，D16和D24是归一化的定点数，写入时深度会被限制在[0, 1]内并量化
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
+pub enum GLDepthFormat
+{
+    D16,
+    /// 和D24S8一样占用4个字节，低8位不使用
+    D24,
+    /// 深度和模板打包在一个u32里，高24位是深度，低8位是模板
+    D24S8,
+    #[default]
+    D32F,
+}
+
+impl GLDepthFormat
+{
+    /// 每个像素的字节数
+    pub fn size(&self) -> usize
+    {
+        match self
+        {
+            GLDepthFormat::D16 => 2,
+            _ => 4,
+        }
+    }
+
+    /// 模板是否和深度存放在一起
+    pub fn has_stencil(&self) -> bool
+    {
+        *self == GLDepthFormat::D24S8
+    }
+}
\ No newline at end of file
@@ -11,8 +11,36 @@ pub enum GLFunction
     CullFace,
 
     DepthTest,
+    /// 不使用近平面和远平面裁剪，而是把深度限制在深度范围内
+    DepthClamp,
     StencilTest,
 
+    /// 剪裁测试，同时作用于光栅化和clear，范围由GLContext::scissor设置
+    ScissorTest,
+
+    /// 线段抗锯齿，覆盖率会乘到alpha上
+    LineSmooth,
+
+    /// 三角形边缘抗锯齿，覆盖率会乘到alpha上
+    PolygonSmooth,
+    /// 三角形的深度偏移，由GLContext::polygon_offset设置
+    PolygonOffsetFill,
+
+    /// 圆形的点，覆盖率会乘到alpha上，没有开启时点是正方形
+    PointSmooth,
+    /// 使用Program::point_size返回的点大小
+    ProgramPointSize,
+
+    /// 自定义裁剪平面，平面由GLContext::clip_plane设置
+    ClipDistance0,
+    ClipDistance1,
+    ClipDistance2,
+    ClipDistance3,
+    ClipDistance4,
+    ClipDistance5,
+    ClipDistance6,
+    ClipDistance7,
+
     Reciprocal_W,
     Z,
 }
\ No newline at end of file
@@ -0,0 +1,14 @@
+/// 生成mipmap时使用的滤波器
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
+pub enum GLMipmapFilter
+{
+    /// 2x2的平均值，奇数长度时是3个像素的多相滤波
+    #[default]
+    Box,
+    /// Kaiser窗的sinc，半径3，保留细节最好
+    Kaiser,
+    /// 半径3的Lanczos，比较锐利，边缘可能有轻微的振铃
+    Lanczos,
+    /// B = C = 1/3的Mitchell-Netravali，介于模糊和锐利之间
+    Mitchell,
+}
\ No newline at end of file
@@ -0,0 +1,11 @@
+/// 三角形的绘制方式，线框和点模式使用当前的线宽和点大小
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
+pub enum GLPolygonMode
+{
+    /// 只绘制顶点
+    Point,
+    /// 只绘制边
+    Line,
+    #[default]
+    Fill,
+}
\ No newline at end of file
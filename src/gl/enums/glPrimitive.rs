@@ -0,0 +1,14 @@
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
+pub enum GLPrimitive
+{
+    Points,
+
+    Lines,
+    LineStrip,
+    LineLoop,
+
+    #[default]
+    Triangles,
+    TriangleStrip,
+    TriangleFan,
+}
\ No newline at end of file
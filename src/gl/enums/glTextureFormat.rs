@@ -0,0 +1,116 @@
+/// 纹理的内部格式，带F的是浮点格式，带UI的是整数格式，D开头的是深度格式，其它是归一化的定点数(unorm)。
+/// 带SRGB的格式按照sRGB编码存放，采样时先转换到线性空间再过滤，alpha始终是线性的。
+/// BC和ETC2是按4x4的块压缩的格式，采样时解码需要的块
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
+pub enum GLTextureFormat
+{
+    R8,
+    RG8,
+    RGB8,
+    #[default]
+    RGBA8,
+    SRGB8,
+    SRGB8A8,
+    /// 半精度浮点数
+    RGBA16F,
+    R32F,
+    RGBA32F,
+    /// 每个通道都是5位指数的无符号浮点数
+    R11G11B10F,
+    /// 采样结果的x是深度
+    D16,
+    D32F,
+    /// 整数格式只能临近采样，用Sampler2D::get_uvec4读取原始的值
+    R32UI,
+    RGBA8UI,
+    /// RGB，可以有1位的alpha，每个块8字节
+    BC1,
+    BC1SRGB,
+    /// BC1的颜色加上4位的alpha，每个块16字节
+    BC2,
+    BC2SRGB,
+    /// BC1的颜色加上插值的alpha，每个块16字节
+    BC3,
+    BC3SRGB,
+    /// 只有红色通道，每个块8字节
+    BC4,
+    /// 红色和绿色通道，常用于法线贴图，每个块16字节
+    BC5,
+    /// 无符号的HDR RGB，解码为半精度浮点数，每个块16字节
+    BC6HUF16,
+    /// 有符号的HDR RGB
+    BC6HSF16,
+    /// 高质量的RGBA，每个块16字节
+    BC7,
+    BC7SRGB,
+    /// 每个块8字节
+    ETC2RGB8,
+    ETC2SRGB8,
+    /// ETC2的颜色加上EAC的alpha，每个块16字节
+    ETC2RGBA8,
+    ETC2SRGB8A8,
+}
+
+impl GLTextureFormat
+{
+    /// 每个纹素的字节数，压缩格式是每个块的字节数
+    pub fn size(&self) -> usize
+    {
+        match self
+        {
+            GLTextureFormat::BC1 | GLTextureFormat::BC1SRGB | GLTextureFormat::BC4 | GLTextureFormat::ETC2RGB8 | GLTextureFormat::ETC2SRGB8 => 8,
+            GLTextureFormat::BC2 | GLTextureFormat::BC2SRGB | GLTextureFormat::BC3 | GLTextureFormat::BC3SRGB | GLTextureFormat::BC5 |
+            GLTextureFormat::BC6HUF16 | GLTextureFormat::BC6HSF16 | GLTextureFormat::BC7 | GLTextureFormat::BC7SRGB |
+            GLTextureFormat::ETC2RGBA8 | GLTextureFormat::ETC2SRGB8A8 => 16,
+            GLTextureFormat::R8 => 1,
+            GLTextureFormat::RG8 | GLTextureFormat::D16 => 2,
+            GLTextureFormat::RGB8 | GLTextureFormat::SRGB8 => 3,
+            GLTextureFormat::RGBA8 | GLTextureFormat::SRGB8A8 | GLTextureFormat::R32F | GLTextureFormat::R11G11B10F |
+            GLTextureFormat::D32F | GLTextureFormat::R32UI | GLTextureFormat::RGBA8UI => 4,
+            GLTextureFormat::RGBA16F => 8,
+            GLTextureFormat::RGBA32F => 16,
+        }
+    }
+
+    /// width * height的纹理需要的字节数，压缩格式不满4x4的块也占用一个完整的块
+    pub fn data_size(&self, width: usize, height: usize) -> usize
+    {
+        if self.is_compressed()
+        {
+            width.div_ceil(4) * height.div_ceil(4) * self.size()
+        }
+        else
+        {
+            width * height * self.size()
+        }
+    }
+
+    pub fn is_float(&self) -> bool
+    {
+        matches!(self, GLTextureFormat::RGBA16F | GLTextureFormat::R32F | GLTextureFormat::RGBA32F | GLTextureFormat::R11G11B10F | GLTextureFormat::D32F)
+    }
+
+    pub fn is_srgb(&self) -> bool
+    {
+        matches!(self, GLTextureFormat::SRGB8 | GLTextureFormat::SRGB8A8 | GLTextureFormat::BC1SRGB | GLTextureFormat::BC2SRGB |
+            GLTextureFormat::BC3SRGB | GLTextureFormat::BC7SRGB | GLTextureFormat::ETC2SRGB8 | GLTextureFormat::ETC2SRGB8A8)
+    }
+
+    pub fn is_depth(&self) -> bool
+    {
+        matches!(self, GLTextureFormat::D16 | GLTextureFormat::D32F)
+    }
+
+    pub fn is_integer(&self) -> bool
+    {
+        matches!(self, GLTextureFormat::R32UI | GLTextureFormat::RGBA8UI)
+    }
+
+    pub fn is_compressed(&self) -> bool
+    {
+        matches!(self, GLTextureFormat::BC1 | GLTextureFormat::BC1SRGB | GLTextureFormat::BC2 | GLTextureFormat::BC2SRGB |
+            GLTextureFormat::BC3 | GLTextureFormat::BC3SRGB | GLTextureFormat::BC4 | GLTextureFormat::BC5 | GLTextureFormat::BC6HUF16 |
+            GLTextureFormat::BC6HSF16 | GLTextureFormat::BC7 | GLTextureFormat::BC7SRGB | GLTextureFormat::ETC2RGB8 |
+            GLTextureFormat::ETC2SRGB8 | GLTextureFormat::ETC2RGBA8 | GLTextureFormat::ETC2SRGB8A8)
+    }
+}
\ No newline at end of file
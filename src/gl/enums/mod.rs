@@ -6,3 +6,9 @@ pub mod glBufferBit;
 pub mod glStencilOp;
 pub mod glBlendFunc;
 pub mod glBlendEquation;
+pub mod glPrimitive;
+pub mod glPolygonMode;
+pub mod glColorFormat;
+pub mod glDepthFormat;
+pub mod glMipmapFilter;
+pub mod glTextureFormat;
\ No newline at end of file
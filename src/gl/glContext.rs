@@ -1,9 +1,13 @@
-use std::ptr::addr_of_mut;
+use std::{collections::HashMap, ops::Range, ptr::addr_of_mut};
 
-use glam::{Vec4, IVec2, Vec3, Vec3A, Vec2, UVec2};
+use glam::{Vec4, IVec2, IVec4, Vec3, Vec3A, Vec2, UVec2};
 use threadPool::ThreadPool;
 
-use super::{shader::{program::Program, varying::Varying, shader::Shader}, enums::{glFunction::GLFunction, glCompareFunc::GLCompareFunc, glSamplePoint::GLSamplePoint, glBufferBit::GLBufferBit, glStencilOp::GLStencilOp, glBlendFunc::GLBlendFunc, glBlendEquation::GLBlendEquation}, glFrameBuffer::GLFrameBuffer, glColor::GLColor, util::is_between};
+use super::{shader::{program::Program, varying::Varying, shader::Shader, fragment::FragmentColor}, enums::{glColorFormat::GLColorFormat, glDepthFormat::GLDepthFormat, glFunction::GLFunction, glCompareFunc::GLCompareFunc, glSamplePoint::GLSamplePoint, glBufferBit::GLBufferBit, glStencilOp::GLStencilOp, glBlendFunc::GLBlendFunc, glBlendEquation::GLBlendEquation, glPrimitive::GLPrimitive, glPolygonMode::GLPolygonMode}, glFrameBuffer::{GLFrameBuffer, MAX_SAMPLES, MAX_COLOR_ATTACHMENTS}, glColor::GLColor, util::is_between, glIndex::GLIndex, sampler::GLFilterFunc};
+
+mod clip;
+mod line;
+mod point;
 
 pub struct GLContext
 {
@@ -13,14 +17,17 @@ pub struct GLContext
     viewport_min: UVec2,
     viewport_max: UVec2,
 
+    scissor_test: bool,
+    scissor_min: IVec2,
+    scissor_max: IVec2,
+
     vertices_pool: Vec<Vec<Vec4>>,
+    elements_pool: Vec<Vec<usize>>,
 
     anisotropic_filter: GLSamplePoint,
 
     pool: ThreadPool,
 
-    color_mask: u32,
-
     alpha_test: bool,
     alpha_func: GLCompareFunc,
     alpha_ref: u8,
@@ -29,6 +36,9 @@ pub struct GLContext
     depth_mask: u32,
     depth_func: GLCompareFunc,
     depth_value: GLFunction,
+    depth_near: f32,
+    depth_far: f32,
+    depth_clamp: bool,
 
     stencil_test: bool,
     stencil_write_mask: u8,
@@ -40,17 +50,32 @@ pub struct GLContext
     depth_fail_op: GLStencilOp,
     all_pass_op: GLStencilOp,
 
-    blend: bool,
-    blend_src_func: GLBlendFunc,
-    blend_dst_func: GLBlendFunc,
-    blend_equation: GLBlendEquation,
+    draw_buffers: [DrawBufferState; MAX_COLOR_ATTACHMENTS],
     blend_color: GLColor,
 
+    line_width: f32,
+    line_smooth: bool,
+
+    polygon_smooth: bool,
+    polygon_mode: GLPolygonMode,
+
+    polygon_offset_fill: bool,
+    polygon_offset_factor: f32,
+    polygon_offset_units: f32,
+
+    point_size: f32,
+    point_smooth: bool,
+    program_point_size: bool,
+
+    clip_planes: [Vec4; 8],
+    clip_plane_mask: u32,
+
     async_draw: bool,
     cull_face: bool,
     front_face_is_ccw: bool,
 
     color: GLColor,
+    color_f: Vec4,
     depth: f32,
     stencil: u8,
 }
@@ -66,14 +91,17 @@ impl GLContext
             viewport_min: UVec2::default(),
             viewport_max: UVec2::new(width, height) - 1,
 
+            scissor_test: false,
+            scissor_min: IVec2::ZERO,
+            scissor_max: UVec2::new(width, height).as_ivec2() - 1,
+
             vertices_pool: Vec::new(),
+            elements_pool: Vec::new(),
 
             anisotropic_filter: GLSamplePoint::X1,
 
             pool: ThreadPool::new(0),
 
-            color_mask: 0xFFFFFFFF,
-
             alpha_test: false,
             alpha_func: GLCompareFunc::Greater,
             alpha_ref: 0x00,
@@ -82,6 +110,9 @@ impl GLContext
             depth_mask: 0xFFFFFFFF,
             depth_func: GLCompareFunc::Less,
             depth_value: GLFunction::Z,
+            depth_near: 0.,
+            depth_far: 1.,
+            depth_clamp: false,
 
             stencil_test: false,
             stencil_write_mask: 0xFF,
@@ -93,17 +124,32 @@ impl GLContext
             depth_fail_op: GLStencilOp::Keep,
             all_pass_op: GLStencilOp::Keep,
 
-            blend: false,
-            blend_src_func: GLBlendFunc::One,
-            blend_dst_func: GLBlendFunc::Zero,
-            blend_equation: GLBlendEquation::Add,
+            draw_buffers: [DrawBufferState::default(); MAX_COLOR_ATTACHMENTS],
             blend_color: GLColor::ONE,
 
+            line_width: 1.0,
+            line_smooth: false,
+
+            polygon_smooth: false,
+            polygon_mode: GLPolygonMode::Fill,
+
+            polygon_offset_fill: false,
+            polygon_offset_factor: 0.,
+            polygon_offset_units: 0.,
+
+            point_size: 1.0,
+            point_smooth: false,
+            program_point_size: false,
+
+            clip_planes: [Vec4::ZERO; 8],
+            clip_plane_mask: 0,
+
             async_draw: false,
             cull_face: false,
             front_face_is_ccw: true,
 
             color: GLColor::ONE,
+            color_f: Vec4::ONE,
             depth: 1.0,
             stencil: 0x00,
         }
@@ -121,6 +167,14 @@ impl GLContext
     pub fn clear_color(&mut self, color: GLColor)
     {
         self.color = color;
+        self.color_f = color.into();
+    }
+
+    /// 浮点版本的清除颜色，浮点格式的颜色附着可以清除为超出[0, 1]的值
+    pub fn clear_color_f(&mut self, color: Vec4)
+    {
+        self.color = color.into();
+        self.color_f = color;
     }
 
     pub fn clear_depth(&mut self, depth: f32)
@@ -133,20 +187,96 @@ impl GLContext
         self.stencil = stencil;
     }
 
-    /// 目前会忽略viewport大小，清除整个帧缓冲
+    /// 设置剪裁矩形，原点在左上角，需要开启GLFunction::ScissorTest
+    pub fn scissor(&mut self, x: i32, y: i32, width: u32, height: u32)
+    {
+        self.scissor_min = IVec2::new(x, y);
+        self.scissor_max = IVec2::new(x + width as i32, y + height as i32) - 1;
+    }
+
+    /// 把src的src_rect缩放复制到dst的dst_rect，矩形是(x0, y0, x1, y1)，不包含x1和y1，
+    /// x1 < x0或者y1 < y0时翻转。mask选择复制颜色、深度、模板中的哪些，颜色附着按照序号一一对应。
+    /// filter只能是Nearest或Linear，复制深度和模板时只能用Nearest。开启剪裁测试时只写入剪裁矩形以内的部分
+    pub fn blit_framebuffer(&self, src: &GLFrameBuffer, src_rect: IVec4, dst: &mut GLFrameBuffer, dst_rect: IVec4, mask: GLBufferBit, filter: GLFilterFunc)
+    {
+        let depth_stencil = mask.contains(GLBufferBit::Depth) || mask.contains(GLBufferBit::Stencil);
+
+        let linear = match filter
+        {
+            GLFilterFunc::Nearest => false,
+            GLFilterFunc::Linear if !depth_stencil => true,
+            _ =>
+            {
+                eprintln!("无效的过滤方式");
+                return;
+            }
+        };
+
+        if src_rect.x == src_rect.z || src_rect.y == src_rect.w || dst_rect.x == dst_rect.z || dst_rect.y == dst_rect.w
+        {
+            return;
+        }
+
+        let mut min = IVec2::max(IVec2::new(dst_rect.x, dst_rect.y).min(IVec2::new(dst_rect.z, dst_rect.w)), IVec2::ZERO);
+        let mut max = IVec2::min(IVec2::new(dst_rect.x, dst_rect.y).max(IVec2::new(dst_rect.z, dst_rect.w)),
+            IVec2::new(dst.get_width() as i32, dst.get_height() as i32)) - 1;
+
+        if self.scissor_test
+        {
+            min = IVec2::max(min, self.scissor_min);
+            max = IVec2::min(max, self.scissor_max);
+        }
+
+        if min.x > max.x || min.y > max.y
+        {
+            return;
+        }
+
+        dst.do_blit(src, src_rect, dst_rect, (min, max), mask, linear);
+    }
+
+    /// 会忽略viewport大小，没有开启剪裁测试时清除整个帧缓冲，否则只清除剪裁矩形以内的部分
     pub fn clear(&self, bits: GLBufferBit, fb: &mut GLFrameBuffer)
     {
-        if bits & GLBufferBit::Color == GLBufferBit::Color
+        if self.scissor_test
+        {
+            let min = IVec2::max(self.scissor_min, IVec2::ZERO);
+            let max = IVec2::min(self.scissor_max, IVec2::new(fb.get_width() as i32, fb.get_height() as i32) - 1);
+
+            if min.x > max.x || min.y > max.y
+            {
+                return;
+            }
+
+            if bits.contains(GLBufferBit::Color)
+            {
+                fb.do_clear_color_rect(self.color, self.color_f, min, max);
+            }
+
+            if bits.contains(GLBufferBit::Depth)
+            {
+                fb.do_clear_depth_rect(self.depth, min, max);
+            }
+
+            if bits.contains(GLBufferBit::Stencil)
+            {
+                fb.do_clear_stencil_rect(self.stencil, min, max);
+            }
+
+            return;
+        }
+
+        if bits.contains(GLBufferBit::Color)
         {
-            fb.do_clear_color(self.color);
+            fb.do_clear_color(self.color, self.color_f);
         }
 
-        if bits & GLBufferBit::Depth == GLBufferBit::Depth
+        if bits.contains(GLBufferBit::Depth)
         {
             fb.do_clear_depth(self.depth);
         }
 
-        if bits & GLBufferBit::Stencil == GLBufferBit::Stencil
+        if bits.contains(GLBufferBit::Stencil)
         {
             fb.do_clear_stencil(self.stencil);
         }
@@ -173,7 +303,10 @@ impl GLContext
 
             GLFunction::Blend =>
             {
-                self.blend = status;
+                for state in self.draw_buffers.iter_mut()
+                {
+                    state.blend = status;
+                }
             }
 
             GLFunction::CullFace =>
@@ -186,11 +319,67 @@ impl GLContext
                 self.depth_test = status;
             }
 
+            GLFunction::DepthClamp =>
+            {
+                self.depth_clamp = status;
+            }
+
             GLFunction::StencilTest =>
             {
                 self.stencil_test = status;
             }
 
+            GLFunction::ScissorTest =>
+            {
+                self.scissor_test = status;
+            }
+
+            GLFunction::LineSmooth =>
+            {
+                self.line_smooth = status;
+            }
+
+            GLFunction::PolygonSmooth =>
+            {
+                self.polygon_smooth = status;
+            }
+
+            GLFunction::PolygonOffsetFill =>
+            {
+                self.polygon_offset_fill = status;
+            }
+
+            GLFunction::PointSmooth =>
+            {
+                self.point_smooth = status;
+            }
+
+            GLFunction::ProgramPointSize =>
+            {
+                self.program_point_size = status;
+            }
+
+            GLFunction::ClipDistance0 |
+            GLFunction::ClipDistance1 |
+            GLFunction::ClipDistance2 |
+            GLFunction::ClipDistance3 |
+            GLFunction::ClipDistance4 |
+            GLFunction::ClipDistance5 |
+            GLFunction::ClipDistance6 |
+            GLFunction::ClipDistance7 =>
+            {
+                let bit = 1 << (func as i32 - GLFunction::ClipDistance0 as i32);
+
+                if status
+                {
+                    self.clip_plane_mask |= bit;
+                }
+                else
+                {
+                    self.clip_plane_mask &= !bit;
+                }
+            }
+
             _ => 
             {
                 return false;
@@ -200,6 +389,41 @@ impl GLContext
         true
     }
 
+    /// 只对第index个颜色附着开启功能，目前只支持GLFunction::Blend
+    pub fn enable_i(&mut self, func: GLFunction, index: usize) -> bool
+    {
+        self.switch_function_i(func, index, true)
+    }
+
+    pub fn disable_i(&mut self, func: GLFunction, index: usize) -> bool
+    {
+        self.switch_function_i(func, index, false)
+    }
+
+    fn switch_function_i(&mut self, func: GLFunction, index: usize, status: bool) -> bool
+    {
+        if index >= MAX_COLOR_ATTACHMENTS
+        {
+            eprintln!("无效的颜色附着序号");
+            return false;
+        }
+
+        match func
+        {
+            GLFunction::Blend =>
+            {
+                self.draw_buffers[index].blend = status;
+            }
+
+            _ =>
+            {
+                return false;
+            }
+        }
+
+        true
+    }
+
     /// 设置绘制线程数量
     pub fn thread_count(&mut self, thread: u32)
     {
@@ -222,12 +446,29 @@ impl GLContext
 
     pub fn color_mask(&mut self, r: bool, g: bool, b: bool, a: bool)
     {
-        self.color_mask = 0xFFFFFFFF;
+        for index in 0..MAX_COLOR_ATTACHMENTS
+        {
+            self.color_mask_i(index, r, g, b, a);
+        }
+    }
+
+    /// 设置第index个颜色附着的颜色掩码
+    pub fn color_mask_i(&mut self, index: usize, r: bool, g: bool, b: bool, a: bool)
+    {
+        if index >= MAX_COLOR_ATTACHMENTS
+        {
+            eprintln!("无效的颜色附着序号");
+            return;
+        }
+
+        let mut color_mask = 0xFFFFFFFF;
 
-        self.color_mask &= if r { 0xFFFFFFFF } else { 0xFFFFFF00 };
-        self.color_mask &= if g { 0xFFFFFFFF } else { 0xFFFF00FF };
-        self.color_mask &= if b { 0xFFFFFFFF } else { 0xFF00FFFF };
-        self.color_mask &= if a { 0xFFFFFFFF } else { 0x00FFFFFF };
+        color_mask &= if r { 0xFFFFFFFF } else { 0xFFFFFF00 };
+        color_mask &= if g { 0xFFFFFFFF } else { 0xFFFF00FF };
+        color_mask &= if b { 0xFFFFFFFF } else { 0xFF00FFFF };
+        color_mask &= if a { 0xFFFFFFFF } else { 0x00FFFFFF };
+
+        self.draw_buffers[index].color_mask = color_mask;
     }
 
     pub fn depth_func(&mut self, func: GLCompareFunc)
@@ -253,6 +494,22 @@ impl GLContext
         }
     }
 
+    /// 设置深度范围，深度值组件是Z时NDC的z会从[-1, 1]映射为near + (far - near) * (z + 1) / 2，和GL相同。
+    /// 裁剪也使用GL的约定，近平面是z = -w，所以投影矩阵应该把z映射到[-1, 1]
+    /// (例如util::perspective_projection或者glam的perspective_rh_gl)。
+    /// 默认的[0, 1]把深度映射到[0, 1]，depth_range(-1., 1.)时深度就是NDC的z。
+    /// 开启DepthClamp时深度会被限制在[near, far]之内
+    ///
+    /// 反向Z：使用z范围反过来的投影矩阵(例如glam的perspective_infinite_reverse_rh，近处是1，远处是0)，
+    /// 然后depth_range(-1., 1.)保持z不变，clear_depth(0.)并且depth_func(GLCompareFunc::Greater)。深度附着需要是D32F，
+    /// 浮点数在0附近的精度正好抵消了透视带来的远处精度损失，适合很大的场景。
+    /// 注意不要用depth_range(1., 0.)来反转，这样在映射时就已经损失了精度
+    pub fn depth_range(&mut self, near: f32, far: f32)
+    {
+        self.depth_near = near;
+        self.depth_far = far;
+    }
+
     pub fn stencil_mask(&mut self, mask: u8)
     {
         self.stencil_write_mask = mask;
@@ -272,6 +529,60 @@ impl GLContext
         self.all_pass_op = all_pass;
     }
 
+    /// 设置线宽，没有开启LineSmooth时会取整
+    pub fn line_width(&mut self, width: f32)
+    {
+        if width > 0.
+        {
+            self.line_width = width;
+        }
+        else
+        {
+            eprintln!("无效的线宽");
+        }
+    }
+
+    /// 设置点大小，没有开启PointSmooth时会取整
+    pub fn point_size(&mut self, size: f32)
+    {
+        if size > 0.
+        {
+            self.point_size = size;
+        }
+        else
+        {
+            eprintln!("无效的点大小");
+        }
+    }
+
+    /// 设置第`index`个自定义裁剪平面，需要开启对应的GLFunction::ClipDistance。
+    /// 平面在裁剪空间中，顶点和平面的点积小于0的部分会被裁剪掉
+    pub fn clip_plane(&mut self, index: usize, plane: Vec4)
+    {
+        if index < self.clip_planes.len()
+        {
+            self.clip_planes[index] = plane;
+        }
+        else
+        {
+            eprintln!("无效的裁剪平面序号");
+        }
+    }
+
+    /// 设置三角形的绘制方式，面剔除在这之前进行
+    pub fn polygon_mode(&mut self, mode: GLPolygonMode)
+    {
+        self.polygon_mode = mode;
+    }
+
+    /// 设置深度偏移，需要开启GLFunction::PolygonOffsetFill，只对填充的三角形有效。
+    /// 偏移直接加在深度值上，深度比较函数是Less时正数代表远离观察者，Greater时相反
+    pub fn polygon_offset(&mut self, factor: f32, units: f32)
+    {
+        self.polygon_offset_factor = factor;
+        self.polygon_offset_units = units;
+    }
+
     /// 设置前面是不是逆时针三角形
     pub fn front_face(&mut self, ccw: bool)
     {
@@ -286,13 +597,43 @@ impl GLContext
 
     pub fn blend_func(&mut self, src_factor: GLBlendFunc, dst_factor: GLBlendFunc)
     {
-        self.blend_src_func = src_factor;
-        self.blend_dst_func = dst_factor;
+        for index in 0..MAX_COLOR_ATTACHMENTS
+        {
+            self.blend_func_i(index, src_factor, dst_factor);
+        }
+    }
+
+    /// 设置第index个颜色附着的混合函数
+    pub fn blend_func_i(&mut self, index: usize, src_factor: GLBlendFunc, dst_factor: GLBlendFunc)
+    {
+        if index >= MAX_COLOR_ATTACHMENTS
+        {
+            eprintln!("无效的颜色附着序号");
+            return;
+        }
+
+        self.draw_buffers[index].src_func = src_factor;
+        self.draw_buffers[index].dst_func = dst_factor;
     }
 
     pub fn blend_equation(&mut self, equation: GLBlendEquation)
     {
-        self.blend_equation = equation;
+        for index in 0..MAX_COLOR_ATTACHMENTS
+        {
+            self.blend_equation_i(index, equation);
+        }
+    }
+
+    /// 设置第index个颜色附着的混合方程
+    pub fn blend_equation_i(&mut self, index: usize, equation: GLBlendEquation)
+    {
+        if index >= MAX_COLOR_ATTACHMENTS
+        {
+            eprintln!("无效的颜色附着序号");
+            return;
+        }
+
+        self.draw_buffers[index].equation = equation;
     }
 
     pub fn blend_color(&mut self, color: GLColor)
@@ -306,6 +647,12 @@ impl GLContext
         GLFrameBuffer::new(self.width as usize, self.height as usize)
     }
 
+    /// 创建多重采样的帧缓冲，支持2、4、8、16个采样点
+    pub fn create_multisample_buffer(&self, samples: GLSamplePoint) -> GLFrameBuffer
+    {
+        GLFrameBuffer::new_multisample(self.width as usize, self.height as usize, samples)
+    }
+
     fn cull_face(&self, vertices: &[Vec4]) -> bool
     {
         let edge1 = Vec3::new(vertices[1].x - vertices[0].x, vertices[1].y - vertices[0].y, vertices[1].z - vertices[0].z);
@@ -317,9 +664,9 @@ impl GLContext
         if self.front_face_is_ccw { view.z < 0. } else { view.z > 0. }
     }
 
-    fn do_blend_color(&self, mut src_color: GLColor, mut dst_color: GLColor) -> GLColor
+    fn do_blend_color(&self, state: &DrawBufferState, mut src_color: GLColor, mut dst_color: GLColor) -> GLColor
     {
-        src_color = match self.blend_src_func
+        src_color = match state.src_func
         {
             GLBlendFunc::Zero => GLColor::ZERO,
             GLBlendFunc::One => src_color,
@@ -341,7 +688,7 @@ impl GLContext
             GLBlendFunc::OneMinusConstAlpha => src_color * (255 - self.blend_color.a),
         };
 
-        dst_color = match self.blend_dst_func
+        dst_color = match state.dst_func
         {
             GLBlendFunc::Zero => GLColor::ZERO,
             GLBlendFunc::One => dst_color,
@@ -363,7 +710,7 @@ impl GLContext
             GLBlendFunc::OneMinusConstAlpha => dst_color * (255 - self.blend_color.a),
         };
 
-        let r = match self.blend_equation
+        let r = match state.equation
         {
             GLBlendEquation::Add => src_color + dst_color,
             GLBlendEquation::Subtract => src_color - dst_color,
@@ -376,146 +723,389 @@ impl GLContext
         r
     }
 
+    /// 浮点版本的混合，结果不会被限制在[0, 1]内
+    fn do_blend_color_f(&self, state: &DrawBufferState, src_color: Vec4, dst_color: Vec4) -> Vec4
+    {
+        let const_color: Vec4 = self.blend_color.into();
+
+        let factor = |func: GLBlendFunc| match func
+        {
+            GLBlendFunc::Zero => Vec4::ZERO,
+            GLBlendFunc::One => Vec4::ONE,
+
+            GLBlendFunc::SrcAlpha => Vec4::splat(src_color.w),
+            GLBlendFunc::DstAlpha => Vec4::splat(dst_color.w),
+            GLBlendFunc::OneMinusSrcAlpha => Vec4::splat(1. - src_color.w),
+            GLBlendFunc::OneMinusDstAlpha => Vec4::splat(1. - dst_color.w),
+
+            GLBlendFunc::SrcColor => src_color,
+            GLBlendFunc::DstColor => dst_color,
+
+            GLBlendFunc::OneMinusSrcColor => Vec4::ONE - src_color,
+            GLBlendFunc::OneMinusDstColor => Vec4::ONE - dst_color,
+
+            GLBlendFunc::ConstColor => const_color,
+            GLBlendFunc::OneMinusConstColor => Vec4::ONE - const_color,
+            GLBlendFunc::ConstAlpha => Vec4::splat(const_color.w),
+            GLBlendFunc::OneMinusConstAlpha => Vec4::splat(1. - const_color.w),
+        };
+
+        let src = src_color * factor(state.src_func);
+        let dst = dst_color * factor(state.dst_func);
+
+        match state.equation
+        {
+            GLBlendEquation::Add => src + dst,
+            GLBlendEquation::Subtract => src - dst,
+            GLBlendEquation::ReverseSubtract => dst - src,
+            GLBlendEquation::Min => src.min(dst),
+            GLBlendEquation::Max => src.max(dst),
+        }
+    }
+
     pub fn draw_arrays<S: Program<T> + Shader<T> + Clone + Send, T: Varying>
-        (&mut self, shader: &mut S, count: usize, offset: usize, fb: &mut GLFrameBuffer)
+        (&mut self, shader: &mut S, mode: GLPrimitive, count: usize, offset: usize, fb: &mut GLFrameBuffer)
     {
         shader.reset();
+        shader.next_n(offset);
+
+        let count = primitive_vertex_count(mode, count);
+        let mut vertices = self.vertex_phase(shader, count, |i| i + offset);
 
-        let mut vertices = self.vertex_phase(shader, count, offset);
+        let mut elements = self.elements_pool.pop().unwrap_or_default();
+        elements.clear();
+        elements.extend(0..count);
 
-        self.pixel_phase(shader, &vertices, fb);
+        self.pixel_phase(shader, mode, &vertices, &elements, fb);
 
         vertices.clear();
         self.vertices_pool.push(vertices);
+        self.elements_pool.push(elements);
     }
 
-    /// 顶点着色器阶段
-    fn vertex_phase<S: Program<T> + Shader<T>, T: Varying>(&mut self, shader: &mut S, count: usize, offset: usize) -> Vec<Vec4>
+    /// 按索引绘制，`range`是使用的索引在`indices`中的范围，`base_vertex`会加到每个索引上。
+    /// 每个不同的顶点只会执行一次顶点着色器，执行顺序为索引第一次出现的顺序
+    pub fn draw_elements<S: Program<T> + Shader<T> + Clone + Send, T: Varying, I: GLIndex>
+        (&mut self, shader: &mut S, mode: GLPrimitive, indices: &[I], range: Range<usize>, base_vertex: usize, fb: &mut GLFrameBuffer)
     {
-        for _ in 0..offset
+        if range.start > range.end || range.end > indices.len()
         {
-            shader.next();
+            eprintln!("索引的范围超出了索引缓冲");
+            return;
         }
 
-        let count = count - count % 3;
-        let mut vertices;
+        shader.reset();
 
-        vertices = self.vertices_pool.pop().unwrap_or(Vec::with_capacity(count));
-        vertices.clear();
+        let count = primitive_vertex_count(mode, range.len());
+        let indices = &indices[range.start..range.start + count];
 
-        for i in 0..count
-        {
-            vertices.push(shader.vertex(i + offset));
+        //索引到已着色顶点的映射，索引可能很稀疏，所以不用按索引范围分配的数组
+        let mut slots = HashMap::with_capacity(count);
+        let mut unique = Vec::new();
 
-            if (i + 1) % 3 == 0
-            {
-                let len = vertices.len();
-                let vert = &mut vertices[len - 3..len];
+        let mut elements = self.elements_pool.pop().unwrap_or_default();
+        elements.clear();
 
-                //剔除w <= 0
-                if vert[0].w <= 0. || vert[1].w <= 0. || vert[2].w <= 0.
-                {
-                    vertices.truncate(len - 3);
-                }
-                else
-                {
-                    //计算w的倒数并存到w里面
-                    let rhw = 1. / vert[0].w;
-                    vert[0] *= rhw;
-                    vert[0].w = rhw;
-
-                    let rhw = 1. / vert[1].w;
-                    vert[1] *= rhw;
-                    vert[1].w = rhw;
-
-                    let rhw = 1. / vert[2].w;
-                    vert[2] *= rhw;
-                    vert[2].w = rhw;
-                }
-            }
+        for index in indices
+        {
+            let slot = *slots.entry(index.as_usize()).or_insert_with(||
+            {
+                unique.push(index.as_usize() + base_vertex);
+                unique.len() - 1
+            });
 
-            shader.next();
+            elements.push(slot);
         }
 
-        vertices
+        let mut vertices = self.vertex_phase(shader, unique.len(), |i| unique[i]);
+
+        self.pixel_phase(shader, mode, &vertices, &elements, fb);
+
+        vertices.clear();
+        self.vertices_pool.push(vertices);
+        self.elements_pool.push(elements);
     }
 
-    /// 片段着色器阶段
-    fn pixel_phase<S: Program<T> + Shader<T> + Clone + Send, T: Varying>(&mut self, shader: &mut S, vertices: &[Vec4], fb: &mut GLFrameBuffer)
+    /// 顶点着色器阶段，第i次调用的顶点序号由`vertex_id(i)`给出，返回裁剪空间中的顶点。
+    /// 多线程时每个线程处理连续的一段顶点，最后按提交顺序合并varying，结果与单线程一致
+    fn vertex_phase<S: Program<T> + Shader<T> + Send, T: Varying>(&mut self, shader: &mut S, count: usize, vertex_id: impl Fn(usize) -> usize + Sync) -> Vec<Vec4>
     {
-        let mut i = 0;
+        let mut vertices;
 
-        let count = self.pool.thread_count() as usize;
-        let mut shaders = Vec::with_capacity(count);
+        vertices = self.vertices_pool.pop().unwrap_or(Vec::with_capacity(count));
+        vertices.clear();
 
-        if self.async_draw
+        let threads = self.pool.thread_count() as usize;
+
+        if !self.async_draw || count < threads * 2
         {
-            for _ in 0..count
+            for i in 0..count
             {
-                shaders.push(shader.clone());
+                vertices.push(shader.vertex(vertex_id(i)));
+                shader.next();
             }
+
+            return vertices;
         }
 
-        while i != vertices.len()
+        let chunk = count.div_ceil(threads);
+
+        //每个线程使用自己的克隆，克隆出来的attribute和主着色器处于同一位置，直接跳到这一段的开头
+        let mut shaders: Vec<_> = (0..count.div_ceil(chunk)).map(|i|
         {
-            let vert = &vertices[i..i+3];
-            let varying = unsafe { &(*(shader as *const S)).get_varying()[i..i+3] };
+            let mut clone = shader.vertex_clone();
+            clone.next_n(i * chunk);
+            clone
+        }).collect();
+
+        vertices.resize(count, Vec4::ZERO);
+
+        let vertex_id = &vertex_id;
 
-            if self.cull_face
+        self.pool.scope(|s|
+        {
+            for (i, (shader, vertices)) in shaders.iter_mut().zip(vertices.chunks_mut(chunk)).enumerate()
             {
-                if self.cull_face(vert)
+                s.spawn(move ||
                 {
-                    i += 3;
-                    continue;
-                }
-            }
+                    let start = i * chunk;
 
-            if self.async_draw
-            {
-                self.triangle_multi_thread(&mut shaders, varying, vert, fb);
-            }
-            else
-            {
-                self.triangle(shader, varying, vert, fb);
+                    for (j, vert) in vertices.iter_mut().enumerate()
+                    {
+                        *vert = shader.vertex(vertex_id(start + j));
+                        shader.next();
+                    }
+                });
             }
-            
-            i += 3;
+        });
+
+        let varyings = shader.get_varying_mut();
+
+        for clone in &mut shaders
+        {
+            varyings.append(clone.get_varying_mut());
         }
+
+        shader.next_n(count);
+
+        vertices
     }
 
-    #[unchecked::unchecked]
-    fn triangle<S: Program<T> + Shader<T>, T: Varying>(&self, shader: &mut S, varying: &[T], vertices: &[Vec4], fb: &mut GLFrameBuffer)
+    /// 片段着色器阶段，`elements`是按提交顺序排列的顶点，指向`vertices`和varying，在这里组装成图元，
+    /// 在裁剪空间中裁剪后再做透视除法并光栅化。目前只有三角形会用到多线程
+    fn pixel_phase<S: Program<T> + Shader<T> + Clone + Send, T: Varying>(&mut self, shader: &mut S, mode: GLPrimitive, vertices: &[Vec4], elements: &[usize], fb: &mut GLFrameBuffer)
     {
-        let mut min = self.viewport_max.as_ivec2();
-        let mut max = self.viewport_min.as_ivec2();
+        let mut primitives = self.elements_pool.pop().unwrap_or_default();
+        primitives.clear();
 
-        let vert = [
-            Vec2::new((1. + vertices[0].x) * self.width as f32 * 0.5 + max.x as f32,
-            (1. - vertices[0].y) * self.height as f32 * 0.5 + max.y as f32),
+        assemble_primitives(mode, elements, &mut primitives);
 
-            Vec2::new((1. + vertices[1].x) * self.width as f32 * 0.5 + max.x as f32,
-            (1. - vertices[1].y) * self.height as f32 * 0.5 + max.y as f32),
+        //不需要裁剪的图元直接使用这里除好的顶点
+        let mut divided = self.vertices_pool.pop().unwrap_or_default();
+        divided.clear();
+        divided.extend(vertices.iter().map(|&vert| self.to_window(vert)));
 
-            Vec2::new((1. + vertices[2].x) * self.width as f32 * 0.5 + max.x as f32,
-            (1. - vertices[2].y) * self.height as f32 * 0.5 + max.y as f32)
-        ];
+        let varyings = unsafe { &(*(shader as *const S)).get_varying() };
 
-        //计算三角形AABB
-        for v in vert
+        match mode
         {
-            min.x = i32::min(min.x, v.x as i32);
-            min.y = i32::min(min.y, v.y as i32);
+            GLPrimitive::Points =>
+            {
+                for &element in primitives.iter()
+                {
+                    //点不做裁剪，中心在视锥体外就丢弃
+                    if self.clip_code(vertices[element]) != 0
+                    {
+                        continue;
+                    }
 
-            max.x = i32::max(max.x, v.x as i32);
-            max.y = i32::max(max.y, v.y as i32);
-        }
+                    self.point(shader, &varyings[element], divided[element], fb);
+                }
+            }
 
-        min = IVec2::max(min, self.viewport_min.as_ivec2());
-        max = IVec2::min(max, self.viewport_max.as_ivec2());
+            GLPrimitive::Lines |
+            GLPrimitive::LineStrip |
+            GLPrimitive::LineLoop =>
+            {
+                for element in primitives.chunks_exact(2)
+                {
+                    let code = [self.clip_code(vertices[element[0]]), self.clip_code(vertices[element[1]])];
 
-        let rhw0 = vertices[0].w;
-        let rhw1 = vertices[1].w;
-        let rhw2 = vertices[2].w;
+                    if code[0] & code[1] != 0
+                    {
+                        continue;
+                    }
+
+                    if code[0] | code[1] == 0
+                    {
+                        let vert = [divided[element[0]], divided[element[1]]];
+                        let varying = [varyings[element[0]], varyings[element[1]]];
+
+                        self.line(shader, &varying, &vert, fb);
+                        continue;
+                    }
+
+                    let mut line = [(vertices[element[0]], varyings[element[0]]), (vertices[element[1]], varyings[element[1]])];
+
+                    if self.clip_line(code[0] | code[1], &mut line)
+                    {
+                        let vert = [self.to_window(line[0].0), self.to_window(line[1].0)];
+                        let varying = [line[0].1, line[1].1];
+
+                        self.line(shader, &varying, &vert, fb);
+                    }
+                }
+            }
+
+            GLPrimitive::Triangles |
+            GLPrimitive::TriangleStrip |
+            GLPrimitive::TriangleFan =>
+            {
+                let count = self.pool.thread_count() as usize;
+                let mut shaders = Vec::with_capacity(count);
+
+                if self.async_draw
+                {
+                    for _ in 0..count
+                    {
+                        shaders.push(shader.clone());
+                    }
+                }
+
+                let mut polygon = Vec::new();
+                let mut temp = Vec::new();
+
+                for element in primitives.chunks_exact(3)
+                {
+                    let code = [
+                        self.clip_code(vertices[element[0]]),
+                        self.clip_code(vertices[element[1]]),
+                        self.clip_code(vertices[element[2]]),
+                    ];
+
+                    //三个顶点都在同一个平面外侧
+                    if code[0] & code[1] & code[2] != 0
+                    {
+                        continue;
+                    }
+
+                    if code[0] | code[1] | code[2] == 0
+                    {
+                        let vert = [divided[element[0]], divided[element[1]], divided[element[2]]];
+                        let varying = [varyings[element[0]], varyings[element[1]], varyings[element[2]]];
+
+                        self.raster_triangle(shader, &mut shaders, &varying, &vert, fb);
+                        continue;
+                    }
+
+                    polygon.clear();
+                    polygon.extend(element.iter().map(|&e| (vertices[e], varyings[e])));
+
+                    self.clip_polygon(code[0] | code[1] | code[2], &mut polygon, &mut temp);
+
+                    //线框和点模式只画裁剪后多边形的边界和顶点，不画三角形扇内部的对角线
+                    if self.polygon_mode != GLPolygonMode::Fill
+                    {
+                        let vert: Vec<_> = polygon.iter().map(|p| self.to_window(p.0)).collect();
+                        let varying: Vec<_> = polygon.iter().map(|p| p.1).collect();
+
+                        self.outline_polygon(shader, &varying, &vert, fb);
+                        continue;
+                    }
+
+                    //裁剪后的凸多边形按三角形扇拆开
+                    for i in 2..polygon.len()
+                    {
+                        let vert = [self.to_window(polygon[0].0), self.to_window(polygon[i - 1].0), self.to_window(polygon[i].0)];
+                        let varying = [polygon[0].1, polygon[i - 1].1, polygon[i].1];
+
+                        self.raster_triangle(shader, &mut shaders, &varying, &vert, fb);
+                    }
+                }
+            }
+        }
+
+        self.elements_pool.push(primitives);
+        self.vertices_pool.push(divided);
+    }
+
+    /// 面剔除之后按照多边形模式光栅化一个已经做过透视除法的三角形
+    fn raster_triangle<S: Program<T> + Shader<T> + Clone + Send, T: Varying>(&mut self, shader: &mut S, shaders: &mut [S], varying: &[T; 3], vert: &[Vec4; 3], fb: &mut GLFrameBuffer)
+    {
+        if self.polygon_mode != GLPolygonMode::Fill
+        {
+            self.outline_polygon(shader, varying, vert, fb);
+            return;
+        }
+
+        //裁剪之后只有退化的三角形会出现w <= 0
+        if vert[0].w <= 0. || vert[1].w <= 0. || vert[2].w <= 0.
+        {
+            return;
+        }
+
+        if self.cull_face && self.cull_face(vert)
+        {
+            return;
+        }
+
+        if self.async_draw
+        {
+            self.triangle_multi_thread(shaders, varying, vert, fb);
+        }
+        else
+        {
+            self.triangle(shader, varying, vert, fb);
+        }
+    }
+
+    /// 线框和点模式，面剔除之后画出已经做过透视除法的凸多边形的边或者顶点
+    fn outline_polygon<S: Program<T> + Shader<T>, T: Varying>(&mut self, shader: &mut S, varying: &[T], vert: &[Vec4], fb: &mut GLFrameBuffer)
+    {
+        if vert.iter().any(|v| v.w <= 0.)
+        {
+            return;
+        }
+
+        //用整个多边形的有向面积判断朝向，和cull_face的叉积同号
+        let area: f32 = (0..vert.len()).map(|i|
+        {
+            let (a, b) = (vert[i], vert[(i + 1) % vert.len()]);
+            a.x * b.y - b.x * a.y
+        }).sum();
+
+        if self.cull_face && if self.front_face_is_ccw { area < 0. } else { area > 0. }
+        {
+            return;
+        }
+
+        if self.polygon_mode == GLPolygonMode::Point
+        {
+            for (varying, vert) in varying.iter().zip(vert)
+            {
+                self.point(shader, varying, *vert, fb);
+            }
+
+            return;
+        }
+
+        for i in 0..vert.len()
+        {
+            let j = (i + 1) % vert.len();
+            self.line(shader, &[varying[i], varying[j]], &[vert[i], vert[j]], fb);
+        }
+    }
+
+    #[unchecked::unchecked]
+    fn triangle<S: Program<T> + Shader<T>, T: Varying>(&self, shader: &mut S, varying: &[T], vertices: &[Vec4], fb: &mut GLFrameBuffer)
+    {
+        //退化成线或者完全在光栅化范围外
+        let Some((edges, min, max)) = self.setup_edges(vertices) else { return };
+        let depth_offset = self.depth_offset(vertices, fb.get_depth_format());
+
+        let rhw0 = vertices[0].w;
+        let rhw1 = vertices[1].w;
+        let rhw2 = vertices[2].w;
 
         let v0 = varying[0] * rhw0;
         let v1 = varying[1] * rhw1;
@@ -538,20 +1128,15 @@ impl GLContext
             z2 = vertices[2].z * rhw2;
         }
 
-        let a = Vec3A::new(vert[2].x - vert[0].x,
-            vert[1].x - vert[0].x,
-            vert[0].x);
-
-        let b = Vec3A::new(vert[2].y - vert[0].y,
-            vert[1].y - vert[0].y,
-            vert[0].y);
-
-        let mut valid;
         let mut some_test_failed;
 
-        let mut varyings = [T::default(); 4];
+        let positions = fb.get_sample_positions();
+        let single_sample = positions.len() == 1;
+        let all_samples = (1 << positions.len()) - 1;
+
+        let mut quad = Quad::default();
+        let mut coverage = [0.; 4];
         let mut screens = [Vec3A::default(); 4];
-        let mut zs = [0.; 4];
         let mut ws = [0.; 4];
 
         let mut y = min.y;
@@ -568,55 +1153,62 @@ impl GLContext
                 let mut quad_x = x as f32 + 0.5;
                 let mut quad_y = y as f32 + 0.5;
 
-                valid = 0;
+                quad.x = x;
+                quad.y = y;
+                quad.valid = 0;
+
                 some_test_failed = false;
 
                 //一次性处理四个像素
                 for i in 0..4
                 {
-                    let screen = barycentric(a, b, Vec2 { x: quad_x, y: quad_y });
+                    let xx = quad_x as i32;
+                    let yy = quad_y as i32;
+
+                    let (screen, _) = edges.barycentric(xx, yy, CENTER);
                     let rhw = rhw0 * screen.x + rhw1 * screen.y + rhw2 * screen.z;
                     let w = 1. / rhw;
                     screens[i] = screen;
                     ws[i] = w;
 
-                    let xx = quad_x as i32;
-                    let yy = quad_y as i32;
+                    //按照左上规则得到被三角形覆盖的采样点，开启PolygonSmooth时根据像素的覆盖率决定
+                    let covered = if self.polygon_smooth
+                    {
+                        coverage[i] = edges.smooth_coverage(xx, yy);
+                        if coverage[i] > 0. { all_samples } else { 0 }
+                    }
+                    else
+                    {
+                        edges.coverage(xx, yy, positions)
+                    };
 
-                    if is_between(xx, min.x, max.x) && is_between(yy, min.y, max.y)
+                    if covered != 0 && is_between(xx, min.x, max.x) && is_between(yy, min.y, max.y)
                     {
-                        //三角形重心坐标出现负值，证明在三角形外
-                        if screen.x < 0. || screen.y < 0. || screen.z < 0.
+                        //单采样时采样点就是像素中心
+                        let depth = |s: usize|
                         {
+                            let (screen, rhw) = if single_sample
+                            {
+                                (screen, rhw)
+                            }
+                            else
+                            {
+                                let (screen, _) = edges.barycentric(xx, yy, positions[s]);
+                                (screen, rhw0 * screen.x + rhw1 * screen.y + rhw2 * screen.z)
+                            };
 
-                        }
-                        else
-                        {
                             let depth = if self.depth_value == GLFunction::Reciprocal_W
                             { rhw }
                             else
-                            { (z0 * screen.x + z1 * screen.y + z2 * screen.z) * w };
-                            zs[i] = depth;
+                            { (z0 * screen.x + z1 * screen.y + z2 * screen.z) * (1. / rhw) };
 
-                            //如果没有alpha test，那么开启early z culling
-                            if !self.alpha_test
-                            {
-                                if let Some(depth_test_failed) = self.do_stencil_depth_test(xx, yy, i as i32, &mut valid, depth, fb)
-                                {
-                                    some_test_failed = depth_test_failed;
-                                }
-                                else
-                                {
-                                    some_test_failed = true;
-                                }
-                            }
-                            else
-                            {
-                                valid |= 1 << i;
-                            }
+                            depth + depth_offset
+                        };
 
-                            inside = true;
-                        }
+                        //不需要late_test时开启early z culling
+                        some_test_failed = self.early_test::<S, T>(&mut quad, i, covered, depth, fb);
+
+                        inside = true;
                     }
 
                     //0-0 1-1 2-0 3-1
@@ -625,77 +1217,16 @@ impl GLContext
                     quad_y += if i % 2 == 0 { 0. } else {  1. };
                 }
 
-                if valid != 0
+                if quad.valid != 0
                 {
-                    //片段着色器分为两部分，sample部分是给纹理采样用的，在这里采样器提前算好mipmap等级
                     for i in 0..4
                     {
-                        varyings[i] = (v0 * screens[i].x + v1 * screens[i].y + v2 * screens[i].z) * ws[i];
-                        shader.sample(&varyings[i]);
+                        quad.varyings[i] = (v0 * screens[i].x + v1 * screens[i].y + v2 * screens[i].z) * ws[i];
                     }
 
-                    shader.compute_level(self.anisotropic_filter as i32);
-
-                    let mut xx = x;
-                    let mut yy = y;
-
-                    for i in 0..4
-                    {
-                        let color = shader.fragment(&varyings[i], IVec2::new(xx, yy));
-
-                        if valid & (1 << i) != 0
-                        {
-                            //开启了alpha test，这里才进行模板和深度测试
-                            if self.alpha_test
-                            {
-                                if compare_value(self.alpha_func, self.alpha_ref, color.a)
-                                {
-                                    match self.do_stencil_depth_test(xx, yy, i as i32, &mut valid, zs[i], fb)
-                                    {
-                                        //模板或者深度测试不通过
-                                        None |
-                                        Some(true) =>
-                                        {
-                                            //0-0 1-1 2-0 3-1
-                                            xx += if i % 2 == 0 { 1 } else { -1 };
-                                            //0-0 1-0 2-1 3-1
-                                            yy += if i % 2 == 0 { 0 } else {  1 };
-                                            continue;
-                                        }
-
-                                        _ => {}
-                                    }
-                                }
-                                else
-                                {
-                                    //0-0 1-1 2-0 3-1
-                                    xx += if i % 2 == 0 { 1 } else { -1 };
-                                    //0-0 1-0 2-1 3-1
-                                    yy += if i % 2 == 0 { 0 } else {  1 };
-                                    continue;
-                                }
-                            }
+                    quad.coverage = if self.polygon_smooth { Some(coverage) } else { None };
 
-                            let keep_one: u32 = fb.get_color(xx, yy).into();
-
-                            //混合
-                            if self.blend
-                            {
-                                let color: u32 = self.do_blend_color(color, fb.get_color(xx, yy)).into();
-                                fb.set_color(xx, yy, GLColor::from((color & self.color_mask) | (keep_one & !self.color_mask)));
-                            }
-                            else
-                            {
-                                let color: u32 = color.into();
-                                fb.set_color(xx, yy, GLColor::from((color & self.color_mask) | (keep_one & !self.color_mask)));
-                            }
-                        }
-
-                        //0-0 1-1 2-0 3-1
-                        xx += if i % 2 == 0 { 1 } else { -1 };
-                        //0-0 1-0 2-1 3-1
-                        yy += if i % 2 == 0 { 0 } else {  1 };
-                    }
+                    self.shade_quad(shader, &quad, fb);
                 }
                 else if inside && !some_test_failed
                 {
@@ -712,33 +1243,11 @@ impl GLContext
 
     //基本同上
     #[unchecked::unchecked]
-    fn triangle_multi_thread<S: Program<T> + Shader<T> + Clone + Send, T: Varying + Send + Sync>(&mut self, shaders: &mut Vec<S>, varying: &[T], vertices: &[Vec4], fb: &mut GLFrameBuffer)
+    fn triangle_multi_thread<S: Program<T> + Shader<T> + Clone + Send, T: Varying + Send + Sync>(&mut self, shaders: &mut [S], varying: &[T], vertices: &[Vec4], fb: &mut GLFrameBuffer)
     {
-        let mut min = self.viewport_max.as_ivec2();
-        let mut max = self.viewport_min.as_ivec2();
-
-        let vert = [
-            Vec2::new((1. + vertices[0].x) * self.width as f32 * 0.5 + max.x as f32,
-            (1. - vertices[0].y) * self.height as f32 * 0.5 + max.y as f32),
-
-            Vec2::new((1. + vertices[1].x) * self.width as f32 * 0.5 + max.x as f32,
-            (1. - vertices[1].y) * self.height as f32 * 0.5 + max.y as f32),
-
-            Vec2::new((1. + vertices[2].x) * self.width as f32 * 0.5 + max.x as f32,
-            (1. - vertices[2].y) * self.height as f32 * 0.5 + max.y as f32)
-        ];
-
-        for v in vert
-        {
-            min.x = i32::min(min.x, v.x as i32);
-            min.y = i32::min(min.y, v.y as i32);
-
-            max.x = i32::max(max.x, v.x as i32);
-            max.y = i32::max(max.y, v.y as i32);
-        }
-
-        min = IVec2::max(min, self.viewport_min.as_ivec2());
-        max = IVec2::min(max, self.viewport_max.as_ivec2());
+        //退化成线或者完全在光栅化范围外
+        let Some((edges, min, max)) = self.setup_edges(vertices) else { return };
+        let depth_offset = self.depth_offset(vertices, fb.get_depth_format());
 
         let rhw0 = vertices[0].w;
         let rhw1 = vertices[1].w;
@@ -765,13 +1274,9 @@ impl GLContext
             z2 = vertices[2].z * rhw2;
         }
 
-        let a = Vec3A::new(vert[2].x - vert[0].x,
-            vert[1].x - vert[0].x,
-            vert[0].x);
-
-        let b = Vec3A::new(vert[2].y - vert[0].y,
-            vert[1].y - vert[0].y,
-            vert[0].y);
+        let positions = fb.get_sample_positions();
+        let single_sample = positions.len() == 1;
+        let all_samples = (1 << positions.len()) - 1;
 
         let pool = unsafe { &mut *addr_of_mut!(self.pool) };
         let threads = pool.thread_count() as i32;
@@ -792,12 +1297,11 @@ impl GLContext
                 {
                     let this = unsafe { std::mem::transmute::<usize, &GLContext>(this) };
 
-                    let mut varyings = [T::default(); 4];
+                    let mut quad = Quad::default();
+                    let mut coverage = [0.; 4];
                     let mut screens = [Vec3A::default(); 4];
-                    let mut zs = [0.; 4];
                     let mut ws = [0.; 4];
 
-                    let mut valid;
                     let mut some_test_failed;
 
                     while y <= max.y
@@ -810,53 +1314,61 @@ impl GLContext
                             let mut quad_x = x as f32 + 0.5;
                             let mut quad_y = y as f32 + 0.5;
 
-                            valid = 0;
+                            quad.x = x;
+                            quad.y = y;
+                            quad.valid = 0;
+
                             some_test_failed = false;
 
                             for i in 0..4
                             {
-                                let screen = barycentric(a, b, Vec2 { x: quad_x, y: quad_y });
+                                let xx = quad_x as i32;
+                                let yy = quad_y as i32;
+
+                                let (screen, _) = edges.barycentric(xx, yy, CENTER);
                                 let rhw = rhw0 * screen.x + rhw1 * screen.y + rhw2 * screen.z;
                                 let w = 1. / rhw;
                                 screens[i] = screen;
                                 ws[i] = w;
 
-                                let xx = quad_x as i32;
-                                let yy = quad_y as i32;
+                                //按照左上规则得到被三角形覆盖的采样点，开启PolygonSmooth时根据像素的覆盖率决定
+                                let covered = if this.polygon_smooth
+                                {
+                                    coverage[i] = edges.smooth_coverage(xx, yy);
+                                    if coverage[i] > 0. { all_samples } else { 0 }
+                                }
+                                else
+                                {
+                                    edges.coverage(xx, yy, positions)
+                                };
 
-                                if is_between(xx, min.x, max.x) && is_between(yy, min.y, max.y)
+                                if covered != 0 && is_between(xx, min.x, max.x) && is_between(yy, min.y, max.y)
                                 {
-                                    //三角形重心坐标出现负值，证明在三角形外
-                                    if screen.x < 0. || screen.y < 0. || screen.z < 0.
-                                    {
-            
-                                    }
-                                    else
+                                    //单采样时采样点就是像素中心
+                                    let depth = |s: usize|
                                     {
-                                        let depth = if this.depth_value == GLFunction::Reciprocal_W
-                                        { rhw }
-                                        else
-                                        { (z0 * screen.x + z1 * screen.y + z2 * screen.z) * w };
-                                        zs[i] = depth;
-            
-                                        if !this.alpha_test
+                                        let (screen, rhw) = if single_sample
                                         {
-                                            if let Some(depth_test_failed) = this.do_stencil_depth_test(xx, yy, i as i32, &mut valid, depth, fb)
-                                            {
-                                                some_test_failed = depth_test_failed;
-                                            }
-                                            else
-                                            {
-                                                some_test_failed = true;
-                                            }
+                                            (screen, rhw)
                                         }
                                         else
                                         {
-                                            valid |= 1 << i;
-                                        }
-            
-                                        inside = true;
-                                    }
+                                            let (screen, _) = edges.barycentric(xx, yy, positions[s]);
+                                            (screen, rhw0 * screen.x + rhw1 * screen.y + rhw2 * screen.z)
+                                        };
+
+                                        let depth = if this.depth_value == GLFunction::Reciprocal_W
+                                        { rhw }
+                                        else
+                                        { (z0 * screen.x + z1 * screen.y + z2 * screen.z) * (1. / rhw) };
+
+                                        depth + depth_offset
+                                    };
+
+                                    //不需要late_test时开启early z culling
+                                    some_test_failed = this.early_test::<S, T>(&mut quad, i, covered, depth, fb);
+
+                                    inside = true;
                                 }
             
                                 //0-0 1-1 2-0 3-1
@@ -865,75 +1377,16 @@ impl GLContext
                                 quad_y += if i % 2 == 0 { 0. } else {  1. };
                             }
 
-                            if valid != 0
+                            if quad.valid != 0
                             {
-                                for i in 0..varyings.len()
-                                {
-                                    varyings[i] = (v0 * screens[i].x + v1 * screens[i].y + v2 * screens[i].z) * ws[i];
-                                    shader.sample(&varyings[i]);
-                                }
-
-                                shader.compute_level(this.anisotropic_filter as i32);
-
-                                let mut xx = x;
-                                let mut yy = y;
-
                                 for i in 0..4
                                 {
-                                    if valid & (1 << i) != 0
-                                    {
-                                        let color = shader.fragment(&varyings[i], IVec2::new(xx, yy));
-
-                                        if this.alpha_test
-                                        {
-                                            if compare_value(this.alpha_func, this.alpha_ref, color.a)
-                                            {
-                                                match this.do_stencil_depth_test(xx, yy, i as i32, &mut valid, zs[i], fb)
-                                                {
-                                                    //模板或者深度测试不通过
-                                                    None |
-                                                    Some(true) =>
-                                                    {
-                                                        //0-0 1-1 2-0 3-1
-                                                        xx += if i % 2 == 0 { 1 } else { -1 };
-                                                        //0-0 1-0 2-1 3-1
-                                                        yy += if i % 2 == 0 { 0 } else {  1 };
-                                                        continue;
-                                                    }
-            
-                                                    _ => {}
-                                                }
-                                            }
-                                            else
-                                            {
-                                                //0-0 1-1 2-0 3-1
-                                                xx += if i % 2 == 0 { 1 } else { -1 };
-                                                //0-0 1-0 2-1 3-1
-                                                yy += if i % 2 == 0 { 0 } else {  1 };
-                                                continue;
-                                            }
-                                        }
+                                    quad.varyings[i] = (v0 * screens[i].x + v1 * screens[i].y + v2 * screens[i].z) * ws[i];
+                                }
 
-                                        let keep_one: u32 = fb.get_color(xx, yy).into();
-            
-                                        if this.blend
-                                        {
-                                            let color: u32 = this.do_blend_color(color, fb.get_color(xx, yy)).into();
-                                            
-                                            fb.set_color(xx, yy, GLColor::from((color & this.color_mask) | (keep_one & !this.color_mask)));
-                                        }
-                                        else
-                                        {
-                                            let color: u32 = color.into();
-                                            fb.set_color(xx, yy, GLColor::from((color & this.color_mask) | (keep_one & !this.color_mask)));
-                                        }
-                                    }
+                                quad.coverage = if this.polygon_smooth { Some(coverage) } else { None };
 
-                                    //0-0 1-1 2-0 3-1
-                                    xx += if i % 2 == 0 { 1 } else { -1 };
-                                    //0-0 1-0 2-1 3-1
-                                    yy += if i % 2 == 0 { 0 } else { 1 };
-                                }
+                                this.shade_quad(shader, &quad, fb);
                             }
                             else if inside && !some_test_failed
                             {
@@ -950,56 +1403,659 @@ impl GLContext
         });
     }
 
-    /// bool为true代表模板测试通过，深度测试失败
-    fn do_stencil_depth_test(&self, xx: i32, yy: i32, i: i32, valid: &mut i32, depth: f32, fb: &mut GLFrameBuffer) -> Option<bool>
+    /// 把三角形顶点转换成24.8的定点数屏幕坐标并建立边函数，返回值包含光栅化时要遍历的包围盒。
+    /// 面积为0或者包围盒为空时返回None
+    fn setup_edges(&self, vertices: &[Vec4]) -> Option<(EdgeFunctions, IVec2, IVec2)>
     {
-        //模板测试
-        if self.stencil_test
+        let mut vert = [FixedPoint::default(); 3];
+
+        for (v, vertex) in vert.iter_mut().zip(vertices)
         {
-            if !compare_value(self.stencil_func, fb.get_stencil(xx, yy) & self.stencil_test_mask,
-            self.stencil_ref & self.stencil_test_mask)
+            let screen = self.to_screen(*vertex) * SUBPIXEL_SCALE;
+            *v = FixedPoint { x: screen.x.round() as i64, y: screen.y.round() as i64 };
+        }
+
+        //统一成面积为正的顺序，左上规则里的“左边”和“上边”才有固定的方向
+        let swapped = edge_function(vert[0], vert[1], vert[2]) < 0;
+
+        if swapped
+        {
+            vert.swap(1, 2);
+        }
+
+        let area = edge_function(vert[0], vert[1], vert[2]);
+
+        if area == 0
+        {
+            return None;
+        }
+
+        let mut edges = EdgeFunctions {
+            vert,
+            bias: [0; 3],
+            rcp_area: 1. / area as f32,
+            rcp_length: [0.; 3],
+            swapped,
+        };
+
+        //第i条边是第i个顶点对面的边
+        for i in 0..3
+        {
+            let (a, b) = (vert[(i + 1) % 3], vert[(i + 2) % 3]);
+            let top_left = (b.y == a.y && b.x > a.x) || b.y < a.y;
+
+            edges.bias[i] = if top_left { 0 } else { -1 };
+
+            let length = Vec2::new((b.x - a.x) as f32, (b.y - a.y) as f32).length();
+            edges.rcp_length[i] = 1. / (length * SUBPIXEL_SCALE);
+        }
+
+        let (raster_min, raster_max) = self.raster_bounds();
+
+        let mut min = raster_max;
+        let mut max = raster_min;
+
+        //计算三角形AABB
+        for v in vert
+        {
+            let pixel = IVec2::new((v.x >> SUBPIXEL_BITS) as i32, (v.y >> SUBPIXEL_BITS) as i32);
+
+            min = IVec2::min(min, pixel);
+            max = IVec2::max(max, pixel);
+        }
+
+        //抗锯齿时边缘外的像素也可能被部分覆盖
+        if self.polygon_smooth
+        {
+            min -= 1;
+            max += 1;
+        }
+
+        min = IVec2::max(min, raster_min);
+        max = IVec2::min(max, raster_max);
+
+        if min.x > max.x || min.y > max.y
+        {
+            return None;
+        }
+
+        Some((edges, min, max))
+    }
+
+    /// 开启PolygonOffsetFill时计算三角形的深度偏移：factor * 深度在屏幕空间的最大斜率 + units * 深度值可分辨的最小差值，
+    /// 最小差值由深度附着的格式决定
+    fn depth_offset(&self, vertices: &[Vec4], format: GLDepthFormat) -> f32
+    {
+        if !self.polygon_offset_fill
+        {
+            return 0.;
+        }
+
+        let p = [self.to_screen(vertices[0]), self.to_screen(vertices[1]), self.to_screen(vertices[2])];
+
+        //Reciprocal_W时深度是1/w，否则是NDC的z，两者在屏幕空间中都是线性的
+        let d = if self.depth_value == GLFunction::Reciprocal_W
+        {
+            [vertices[0].w, vertices[1].w, vertices[2].w]
+        }
+        else
+        {
+            [vertices[0].z, vertices[1].z, vertices[2].z]
+        };
+
+        let e1 = p[1] - p[0];
+        let e2 = p[2] - p[0];
+        let area = e1.x * e2.y - e2.x * e1.y;
+
+        let slope = if area != 0.
+        {
+            let dzdx = ((d[1] - d[0]) * e2.y - (d[2] - d[0]) * e1.y) / area;
+            let dzdy = ((d[2] - d[0]) * e1.x - (d[1] - d[0]) * e2.x) / area;
+
+            f32::max(dzdx.abs(), dzdy.abs())
+        }
+        else
+        {
+            0.
+        };
+
+        //定点深度缓冲的最小差值是固定的，浮点深度缓冲的最小差值取决于三角形深度的最大指数
+        let r = match format
+        {
+            GLDepthFormat::D16 => 1. / 65535.,
+            GLDepthFormat::D24 | GLDepthFormat::D24S8 => 1. / 16777215.,
+            GLDepthFormat::D32F =>
+            {
+                let max_depth = f32::max(d[0].abs(), f32::max(d[1].abs(), d[2].abs()));
+                if max_depth > 0. { 2f32.powi(max_depth.log2().floor() as i32 - 23) } else { f32::EPSILON }
+            }
+        };
+
+        self.polygon_offset_factor * slope + self.polygon_offset_units * r
+    }
+
+    /// 光栅化的范围，是视口和剪裁矩形的交集，包含两端
+    #[inline(always)]
+    fn raster_bounds(&self) -> (IVec2, IVec2)
+    {
+        let min = self.viewport_min.as_ivec2();
+        let max = self.viewport_max.as_ivec2();
+
+        if self.scissor_test
+        {
+            (IVec2::max(min, self.scissor_min), IVec2::min(max, self.scissor_max))
+        }
+        else
+        {
+            (min, max)
+        }
+    }
+
+    /// 透视除法之后把z从[-1, 1]按照深度范围映射到[near, far]，x和y留到光栅化时再转换
+    #[inline(always)]
+    fn to_window(&self, vert: Vec4) -> Vec4
+    {
+        let mut vert = perspective_divide(vert);
+        vert.z = self.depth_near + (self.depth_far - self.depth_near) * (vert.z + 1.) * 0.5;
+
+        vert
+    }
+
+    /// NDC坐标转换到屏幕坐标
+    #[inline(always)]
+    fn to_screen(&self, vert: Vec4) -> Vec2
+    {
+        Vec2::new((1. + vert.x) * self.width as f32 * 0.5 + self.viewport_min.x as f32,
+        (1. - vert.y) * self.height as f32 * 0.5 + self.viewport_min.y as f32)
+    }
+
+    /// 开启alpha test，或者片段着色器声明了会丢弃片段、输出深度时，模板和深度测试只能在片段着色之后进行
+    #[inline(always)]
+    fn late_test<S: Program<T>, T: Varying>(&self) -> bool
+    {
+        self.alpha_test || S::DISCARD || S::FRAGMENT_DEPTH
+    }
+
+    /// 开启DepthClamp时把深度限制在深度范围内
+    #[inline(always)]
+    fn clamp_depth(&self, depth: f32) -> f32
+    {
+        if self.depth_clamp && self.depth_value == GLFunction::Z
+        {
+            depth.clamp(f32::min(self.depth_near, self.depth_far), f32::max(self.depth_near, self.depth_far))
+        }
+        else
+        {
+            depth
+        }
+    }
+
+    /// 对第i个像素中被覆盖的采样点`covered`进行测试，第s个采样点的深度由`depth(s)`给出。
+    /// 如果不需要late_test，那么在片段着色之前进行模板和深度测试(early z)，否则只标记为有效。
+    /// 需要先设置好`quad`的坐标，返回值为true代表有采样点测试不通过
+    #[inline(always)]
+    fn early_test<S: Program<T>, T: Varying>(&self, quad: &mut Quad<T>, i: usize, covered: u32, depth: impl Fn(usize) -> f32, fb: &mut GLFrameBuffer) -> bool
+    {
+        let xx = quad.x + (i & 1) as i32;
+        let yy = quad.y + (i >> 1) as i32;
+        let late_test = self.late_test::<S, T>();
+
+        let mut passed = 0;
+        let mut failed = false;
+
+        for s in 0..fb.get_samples()
+        {
+            if covered & (1 << s) == 0
+            {
+                continue;
+            }
+
+            let z = self.clamp_depth(depth(s));
+            quad.zs[i][s] = z;
+
+            if late_test || self.do_stencil_depth_test(xx, yy, s, z, fb) == Some(false)
+            {
+                passed |= 1 << s;
+            }
+            else
+            {
+                failed = true;
+            }
+        }
+
+        quad.masks[i] = passed;
+
+        if passed != 0
+        {
+            quad.valid |= 1 << i;
+        }
+
+        failed
+    }
+
+    /// 对一个2x2的像素块执行片段着色器，然后进行丢弃、alpha测试、模板深度测试以及混合
+    fn shade_quad<S: Program<T> + Shader<T>, T: Varying>(&self, shader: &mut S, quad: &Quad<T>, fb: &mut GLFrameBuffer)
+    {
+        let valid = quad.valid;
+        let late_test = self.late_test::<S, T>();
+        let attachments = fb.color_attachment_mask();
+
+        //片段着色器分为两部分，sample部分是给纹理采样用的，在这里采样器提前算好mipmap等级
+        for i in 0..4
+        {
+            if let Some(point_coords) = &quad.point_coords
+            {
+                shader.point_coord(point_coords[i]);
+            }
+
+            shader.sample(&quad.varyings[i]);
+        }
+
+        shader.compute_level(self.anisotropic_filter as i32);
+
+        for i in 0..4
+        {
+            let xx = quad.x + (i & 1);
+            let yy = quad.y + (i >> 1);
+
+            if let Some(point_coords) = &quad.point_coords
+            {
+                shader.point_coord(point_coords[i as usize]);
+            }
+
+            let output = shader.fragment_output(&quad.varyings[i as usize], IVec2::new(xx, yy));
+
+            //违反了Program::DISCARD和FRAGMENT_DEPTH的约定，early z已经写入了深度和模板，这里丢弃或者改深度都来不及了
+            debug_assert!(S::DISCARD || !output.discarded, "片段着色器丢弃了片段，但是没有设置Program::DISCARD");
+            debug_assert!(S::FRAGMENT_DEPTH || output.depth.is_none(), "片段着色器输出了深度，但是没有设置Program::FRAGMENT_DEPTH");
+
+            if valid & (1 << i) == 0 || output.discarded
+            {
+                continue;
+            }
+
+            let mut colors = output.colors;
+
+            if let Some(coverage) = &quad.coverage
+            {
+                for color in colors.iter_mut()
+                {
+                    color.apply_coverage(coverage[i as usize]);
+                }
+            }
+
+            let mut mask = quad.masks[i as usize];
+
+            //需要late_test时，这里才进行模板和深度测试
+            if late_test
+            {
+                if self.alpha_test && !compare_value(self.alpha_func, self.alpha_ref, colors[0].to_color().a)
+                {
+                    continue;
+                }
+
+                //片段着色器输出的深度对所有采样点都一样
+                let depth = output.depth.map(|depth| self.clamp_depth(depth));
+
+                for s in 0..fb.get_samples()
+                {
+                    //模板或者深度测试不通过
+                    if mask & (1 << s) != 0 && self.do_stencil_depth_test(xx, yy, s, depth.unwrap_or(quad.zs[i as usize][s]), fb) != Some(false)
+                    {
+                        mask &= !(1 << s);
+                    }
+                }
+            }
+
+            //片段着色器每个像素只执行一次，结果写入所有通过测试的采样点
+            for s in 0..fb.get_samples()
             {
-                do_stencil_op(self.stencil_fail_op, xx, yy, self.stencil_write_mask, self.stencil_ref, fb);
-                return None;
+                if mask & (1 << s) == 0
+                {
+                    continue;
+                }
+
+                //每个颜色附着使用各自的混合状态和颜色掩码
+                for (index, &color) in colors.iter().enumerate()
+                {
+                    if attachments & (1 << index) == 0
+                    {
+                        continue;
+                    }
+
+                    let state = &self.draw_buffers[index];
+
+                    //RGBA8格式并且输出的是GLColor时按照整数计算，其它情况按照浮点数计算
+                    let color = match color
+                    {
+                        FragmentColor::Unorm(color) if fb.get_color_format(index) == GLColorFormat::RGBA8 => color,
+                        color =>
+                        {
+                            self.write_color_f(index, xx, yy, s, color.to_vec4(), fb);
+                            continue;
+                        }
+                    };
+
+                    let keep_one: u32 = fb.get_sample_color_at(index, xx, yy, s).into();
+
+                    //混合
+                    if state.blend
+                    {
+                        let color: u32 = self.do_blend_color(state, color, fb.get_sample_color_at(index, xx, yy, s)).into();
+                        fb.set_sample_color_at(index, xx, yy, s, GLColor::from((color & state.color_mask) | (keep_one & !state.color_mask)));
+                    }
+                    else
+                    {
+                        let color: u32 = color.into();
+                        fb.set_sample_color_at(index, xx, yy, s, GLColor::from((color & state.color_mask) | (keep_one & !state.color_mask)));
+                    }
+                }
             }
         }
+    }
+
+    /// 按照浮点数混合并写入一个采样点，定点格式的颜色附着会先把颜色限制在[0, 1]内
+    fn write_color_f(&self, index: usize, xx: i32, yy: i32, sample: usize, mut color: Vec4, fb: &mut GLFrameBuffer)
+    {
+        let state = &self.draw_buffers[index];
+        let dst_color = fb.get_sample_color_f_at(index, xx, yy, sample);
+
+        if !fb.get_color_format(index).is_float()
+        {
+            color = color.clamp(Vec4::ZERO, Vec4::ONE);
+        }
 
-        //深度测试
+        if state.blend
+        {
+            color = self.do_blend_color_f(state, color, dst_color);
+        }
+
+        fb.set_sample_color_f_at(index, xx, yy, sample, Vec4::select(state.channel_mask().cmpne(Vec4::ZERO), color, dst_color));
+    }
+
+    /// 对一个采样点进行模板和深度测试，None代表模板测试失败，bool为true代表模板测试通过，深度测试失败
+    fn do_stencil_depth_test(&self, xx: i32, yy: i32, sample: usize, depth: f32, fb: &mut GLFrameBuffer) -> Option<bool>
+    {
+        //模板测试
+        if self.stencil_test && !compare_value(self.stencil_func, fb.get_sample_stencil(xx, yy, sample) & self.stencil_test_mask,
+            self.stencil_ref & self.stencil_test_mask)
+        {
+            do_stencil_op(self.stencil_fail_op, xx, yy, sample, self.stencil_write_mask, self.stencil_ref, fb);
+            return None;
+        }
+
+        //深度测试，先按照深度附着的格式量化
         if self.depth_test
         {
-            if compare_value(self.depth_func, fb.get_depth(xx, yy), depth)
+            let depth = fb.quantize_depth(depth);
+
+            if compare_value(self.depth_func, fb.get_sample_depth(xx, yy, sample), depth)
             {
-                if self.depth_mask != 0 { fb.set_depth(xx, yy, depth); }
-                if self.stencil_test { do_stencil_op(self.all_pass_op, xx, yy, self.stencil_write_mask, self.stencil_ref, fb); }
-                *valid |= 1 << i;
+                if self.depth_mask != 0 { fb.set_sample_depth(xx, yy, sample, depth); }
+                if self.stencil_test { do_stencil_op(self.all_pass_op, xx, yy, sample, self.stencil_write_mask, self.stencil_ref, fb); }
             }
             else
             {
-                if self.stencil_test { do_stencil_op(self.depth_fail_op, xx, yy, self.stencil_write_mask, self.stencil_ref, fb); }
+                if self.stencil_test { do_stencil_op(self.depth_fail_op, xx, yy, sample, self.stencil_write_mask, self.stencil_ref, fb); }
                 return Some(true);
             }
         }
         else
         {
-            if self.stencil_test { do_stencil_op(self.all_pass_op, xx, yy, self.stencil_write_mask, self.stencil_ref, fb); }
-            *valid |= 1 << i;
+            if self.stencil_test { do_stencil_op(self.all_pass_op, xx, yy, sample, self.stencil_write_mask, self.stencil_ref, fb); }
         }
 
         return Some(false);
     }
 }
 
-/// 计算重心坐标
+/// 每个颜色附着独立的混合状态和颜色掩码
+#[derive(Clone, Copy)]
+struct DrawBufferState
+{
+    blend: bool,
+    src_func: GLBlendFunc,
+    dst_func: GLBlendFunc,
+    equation: GLBlendEquation,
+    color_mask: u32,
+}
+
+impl DrawBufferState
+{
+    /// 颜色掩码展开到每个通道，不为0代表写入
+    #[inline(always)]
+    fn channel_mask(&self) -> Vec4
+    {
+        let [r, g, b, a] = self.color_mask.to_le_bytes();
+        Vec4::new(r as f32, g as f32, b as f32, a as f32)
+    }
+}
+
+impl Default for DrawBufferState
+{
+    fn default() -> Self
+    {
+        Self {
+            blend: false,
+            src_func: GLBlendFunc::One,
+            dst_func: GLBlendFunc::Zero,
+            equation: GLBlendEquation::Add,
+            color_mask: 0xFFFFFFFF,
+        }
+    }
+}
+
+/// 光栅化得到的2x2像素块，第i个像素位于(x + i % 2, y + i / 2)
+#[derive(Default)]
+struct Quad<T: Varying>
+{
+    x: i32,
+    y: i32,
+
+    /// 第i位代表第i个像素被图元覆盖
+    valid: i32,
+
+    /// 每个像素中被覆盖并且通过了测试的采样点
+    masks: [u32; 4],
+
+    varyings: [T; 4],
+
+    /// 每个采样点的深度
+    zs: [[f32; MAX_SAMPLES]; 4],
+
+    /// 抗锯齿时像素被覆盖的比例，会乘到alpha上，需要开启混合才能看到效果
+    coverage: Option<[f32; 4]>,
+
+    /// 绘制点时像素在点精灵内的坐标
+    point_coords: Option<[Vec2; 4]>,
+}
+
+/// 像素中心在像素内的位置
+const CENTER: Vec2 = Vec2::new(0.5, 0.5);
+
+/// 屏幕坐标的小数部分位数
+const SUBPIXEL_BITS: i64 = 8;
+const SUBPIXEL_SCALE: f32 = (1 << SUBPIXEL_BITS) as f32;
+
+/// 定点数屏幕坐标
+#[derive(Clone, Copy, Default)]
+struct FixedPoint
+{
+    x: i64,
+    y: i64,
+}
+
+/// 三角形三条边的定点数边函数
+#[derive(Clone, Copy)]
+struct EdgeFunctions
+{
+    /// 已经统一成面积为正的顺序
+    vert: [FixedPoint; 3],
+
+    /// 不是左边或上边的边要减1，这样像素中心正好落在边上时只属于一个三角形
+    bias: [i64; 3],
+    rcp_area: f32,
+
+    /// 把边函数换算成到边的距离(像素)
+    rcp_length: [f32; 3],
+
+    /// 为了让面积为正交换过第1和第2个顶点
+    swapped: bool,
+}
+
+impl EdgeFunctions
+{
+    /// 计算像素(x, y)内`pos`处的重心坐标(按原来的顶点顺序)以及是否被三角形覆盖。
+    /// 三角形外的像素也会返回重心坐标，2x2像素块里的辅助像素要用来计算mipmap等级
+    #[inline(always)]
+    fn barycentric(&self, x: i32, y: i32, pos: Vec2) -> (Vec3A, bool)
+    {
+        let p = FixedPoint {
+            x: ((x as i64) << SUBPIXEL_BITS) + (pos.x * SUBPIXEL_SCALE) as i64,
+            y: ((y as i64) << SUBPIXEL_BITS) + (pos.y * SUBPIXEL_SCALE) as i64,
+        };
+
+        let e0 = edge_function(self.vert[1], self.vert[2], p);
+        let e1 = edge_function(self.vert[2], self.vert[0], p);
+        let e2 = edge_function(self.vert[0], self.vert[1], p);
+
+        let covered = e0 + self.bias[0] >= 0 && e1 + self.bias[1] >= 0 && e2 + self.bias[2] >= 0;
+
+        let screen = if self.swapped
+        {
+            Vec3A::new(e0 as f32, e2 as f32, e1 as f32)
+        }
+        else
+        {
+            Vec3A::new(e0 as f32, e1 as f32, e2 as f32)
+        };
+
+        (screen * self.rcp_area, covered)
+    }
+
+    /// 根据像素中心到三条边的距离估算像素被三角形覆盖的比例
+    #[inline(always)]
+    fn smooth_coverage(&self, x: i32, y: i32) -> f32
+    {
+        let half = 1 << (SUBPIXEL_BITS - 1);
+        let p = FixedPoint { x: ((x as i64) << SUBPIXEL_BITS) + half, y: ((y as i64) << SUBPIXEL_BITS) + half };
+
+        let mut coverage = 1.;
+
+        for i in 0..3
+        {
+            let distance = edge_function(self.vert[(i + 1) % 3], self.vert[(i + 2) % 3], p) as f32 * self.rcp_length[i];
+            coverage *= (distance + 0.5).clamp(0., 1.);
+        }
+
+        coverage
+    }
+
+    /// 像素(x, y)中被三角形覆盖的采样点，第s位对应`positions[s]`
+    #[inline(always)]
+    fn coverage(&self, x: i32, y: i32, positions: &[Vec2]) -> u32
+    {
+        let mut mask = 0;
+
+        for (s, pos) in positions.iter().enumerate()
+        {
+            if self.barycentric(x, y, *pos).1
+            {
+                mask |= 1 << s;
+            }
+        }
+
+        mask
+    }
+}
+
+/// 点`p`在边`a`->`b`的哪一侧，也是三角形abp面积的两倍
 #[inline(always)]
-fn barycentric(mut a: Vec3A, mut b: Vec3A, p: Vec2) -> Vec3A
+fn edge_function(a: FixedPoint, b: FixedPoint, p: FixedPoint) -> i64
 {
-    a.z -= p.x;
-    b.z -= p.y;
+    (b.x - a.x) * (p.y - a.y) - (b.y - a.y) * (p.x - a.x)
+}
 
-    let u = a.cross(b);
+/// 去掉不足一个图元的多余顶点
+fn primitive_vertex_count(mode: GLPrimitive, count: usize) -> usize
+{
+    match mode
+    {
+        GLPrimitive::Points => count,
+        GLPrimitive::Lines => count - count % 2,
+        GLPrimitive::LineStrip | GLPrimitive::LineLoop => if count < 2 { 0 } else { count },
+        GLPrimitive::Triangles => count - count % 3,
+        GLPrimitive::TriangleStrip | GLPrimitive::TriangleFan => if count < 3 { 0 } else { count },
+    }
+}
+
+/// 把按提交顺序排列的顶点组装成图元，点每个一组，线段每两个一组，三角形每三个一组。
+/// 三角形带中的奇数个三角形会交换前两个顶点，使所有三角形的绕序保持一致，面剔除才能正常工作
+fn assemble_primitives(mode: GLPrimitive, elements: &[usize], primitives: &mut Vec<usize>)
+{
+    let count = primitive_vertex_count(mode, elements.len());
+
+    match mode
+    {
+        GLPrimitive::Points |
+        GLPrimitive::Lines |
+        GLPrimitive::Triangles =>
+        {
+            primitives.extend_from_slice(&elements[..count]);
+        }
+
+        GLPrimitive::LineStrip |
+        GLPrimitive::LineLoop =>
+        {
+            for i in 1..count
+            {
+                primitives.extend_from_slice(&[elements[i - 1], elements[i]]);
+            }
+
+            if mode == GLPrimitive::LineLoop && count > 1
+            {
+                primitives.extend_from_slice(&[elements[count - 1], elements[0]]);
+            }
+        }
+
+        GLPrimitive::TriangleStrip =>
+        {
+            for i in 2..count
+            {
+                if i % 2 == 0
+                {
+                    primitives.extend_from_slice(&[elements[i - 2], elements[i - 1], elements[i]]);
+                }
+                else
+                {
+                    primitives.extend_from_slice(&[elements[i - 1], elements[i - 2], elements[i]]);
+                }
+            }
+        }
+
+        GLPrimitive::TriangleFan =>
+        {
+            for i in 2..count
+            {
+                primitives.extend_from_slice(&[elements[0], elements[i - 1], elements[i]]);
+            }
+        }
+    }
+}
+
+/// 透视除法，w <= 0的顶点原样返回，这样的顶点只会出现在被裁剪的图元里
+#[inline(always)]
+fn perspective_divide(mut vert: Vec4) -> Vec4
+{
+    if vert.w > 0.
+    {
+        //计算w的倒数并存到w里面
+        let rhw = 1. / vert.w;
+        vert *= rhw;
+        vert.w = rhw;
+    }
 
-    let z = 1. / u.z;
-    Vec3A::new(1. - (u.x + u.y) * z, u.y * z, u.x * z)
+    vert
 }
 
 pub fn compare_value<T: PartialOrd>(func: GLCompareFunc, old: T, new: T) -> bool
@@ -1012,9 +2068,9 @@ pub fn compare_value<T: PartialOrd>(func: GLCompareFunc, old: T, new: T) -> bool
     }
 }
 
-fn do_stencil_op(op: GLStencilOp, x: i32, y: i32, write_mask: u8, ref_value: u8, fb: &mut GLFrameBuffer)
+fn do_stencil_op(op: GLStencilOp, x: i32, y: i32, sample: usize, write_mask: u8, ref_value: u8, fb: &mut GLFrameBuffer)
 {
-    let keep_one = fb.get_stencil(x, y) & !write_mask;
+    let keep_one = fb.get_sample_stencil(x, y, sample) & !write_mask;
 
     match op
     {
@@ -1022,42 +2078,42 @@ fn do_stencil_op(op: GLStencilOp, x: i32, y: i32, write_mask: u8, ref_value: u8,
 
         GLStencilOp::Zero =>
         {
-            fb.set_stencil(x, y, 0 | keep_one);
+            fb.set_sample_stencil(x, y, sample, keep_one);
         }
 
         GLStencilOp::Replace =>
         {
-            fb.set_stencil(x, y, (ref_value & write_mask) | keep_one);
+            fb.set_sample_stencil(x, y, sample, (ref_value & write_mask) | keep_one);
         }
 
         GLStencilOp::Increase =>
         {
-            fb.set_stencil(x, y, (fb.get_stencil(x, y).saturating_add(1) & write_mask) | keep_one);
+            fb.set_sample_stencil(x, y, sample, (fb.get_sample_stencil(x, y, sample).saturating_add(1) & write_mask) | keep_one);
         }
 
         GLStencilOp::IncreaseWrap =>
         {
-            fb.set_stencil(x, y, (fb.get_stencil(x, y).wrapping_add(1) & write_mask) | keep_one);
+            fb.set_sample_stencil(x, y, sample, (fb.get_sample_stencil(x, y, sample).wrapping_add(1) & write_mask) | keep_one);
         }
 
         GLStencilOp::Decrease =>
         {
-            fb.set_stencil(x, y, (fb.get_stencil(x, y).saturating_sub(1) & write_mask) | keep_one);
+            fb.set_sample_stencil(x, y, sample, (fb.get_sample_stencil(x, y, sample).saturating_sub(1) & write_mask) | keep_one);
         }
 
         GLStencilOp::DecreaseWrap =>
         {
-            fb.set_stencil(x, y, (fb.get_stencil(x, y).wrapping_sub(1) & write_mask) | keep_one);
+            fb.set_sample_stencil(x, y, sample, (fb.get_sample_stencil(x, y, sample).wrapping_sub(1) & write_mask) | keep_one);
         }
 
         GLStencilOp::Invert =>
         {
-            fb.set_stencil(x, y, (!fb.get_stencil(x, y) & write_mask) | keep_one);
+            fb.set_sample_stencil(x, y, sample, (!fb.get_sample_stencil(x, y, sample) & write_mask) | keep_one);
         }
     }
 }
 
-fn _do_stencil_op_empty(_: GLStencilOp, _: i32, _: i32, _: u8, _: u8, _: &mut GLFrameBuffer)
+fn _do_stencil_op_empty(_: GLStencilOp, _: i32, _: i32, _: usize, _: u8, _: u8, _: &mut GLFrameBuffer)
 {
 
 }
\ No newline at end of file
@@ -0,0 +1,167 @@
+use glam::Vec4;
+
+use crate::gl::shader::varying::Varying;
+
+use super::GLContext;
+
+/// 左右上下四个裁剪平面放在保护带上，x和y在[-GUARD_BAND * w, GUARD_BAND * w]以内的图元
+/// 直接交给光栅化，只限制包围盒到视口，这样大部分和屏幕边缘相交的三角形不需要裁剪
+const GUARD_BAND: f32 = 8.;
+
+/// 裁剪空间中的视锥体平面，顶点和平面的点积 >= 0 代表在内侧。
+/// 顺序是近、远、左、右、下、上，第i个平面对应裁剪码的第i位，之后的8位是自定义裁剪平面
+const FRUSTUM_PLANES: [Vec4; 6] = [
+    Vec4::new(0., 0., 1., 1.),
+    Vec4::new(0., 0., -1., 1.),
+    Vec4::new(1., 0., 0., GUARD_BAND),
+    Vec4::new(-1., 0., 0., GUARD_BAND),
+    Vec4::new(0., 1., 0., GUARD_BAND),
+    Vec4::new(0., -1., 0., GUARD_BAND),
+];
+
+const PLANE_COUNT: usize = FRUSTUM_PLANES.len() + 8;
+
+/// 开启DepthClamp时代替近平面，保证裁剪后的顶点w > 0
+const W_EPSILON: f32 = 1e-5;
+
+impl GLContext
+{
+    /// 顶点到第i个裁剪平面的距离，小于0代表在外侧，和裁剪码的第i位对应。
+    /// 开启DepthClamp时不使用近平面和远平面，只裁剪掉w接近0以及小于0的部分
+    #[inline(always)]
+    fn distance(&self, i: usize, vert: Vec4) -> f32
+    {
+        match i
+        {
+            0 if self.depth_clamp => vert.w - W_EPSILON,
+            1 if self.depth_clamp => 0.,
+            _ if i < FRUSTUM_PLANES.len() => FRUSTUM_PLANES[i].dot(vert),
+            _ => self.clip_planes[i - FRUSTUM_PLANES.len()].dot(vert),
+        }
+    }
+
+    /// 计算顶点的裁剪码，第i位为1代表顶点在第i个平面的外侧
+    #[inline(always)]
+    pub(super) fn clip_code(&self, vert: Vec4) -> u32
+    {
+        let mut code = 0;
+
+        for i in 0..FRUSTUM_PLANES.len()
+        {
+            if self.distance(i, vert) < 0.
+            {
+                code |= 1 << i;
+            }
+        }
+
+        if self.clip_plane_mask != 0
+        {
+            for (i, plane) in self.clip_planes.iter().enumerate()
+            {
+                if self.clip_plane_mask & (1 << i) != 0 && plane.dot(vert) < 0.
+                {
+                    code |= 1 << (i + FRUSTUM_PLANES.len());
+                }
+            }
+        }
+
+        code
+    }
+
+    /// 用Sutherland–Hodgman算法依次裁剪掉`polygon`在`mask`中各个平面外侧的部分，
+    /// 新顶点的varying在裁剪空间中线性插值，结果保存在`polygon`里，少于3个顶点时代表被完全裁剪
+    pub(super) fn clip_polygon<T: Varying>(&self, mask: u32, polygon: &mut Vec<(Vec4, T)>, temp: &mut Vec<(Vec4, T)>)
+    {
+        for i in 0..PLANE_COUNT
+        {
+            if mask & (1 << i) == 0
+            {
+                continue;
+            }
+
+
+            temp.clear();
+
+            for j in 0..polygon.len()
+            {
+                let a = polygon[j];
+                let b = polygon[(j + 1) % polygon.len()];
+
+                let da = self.distance(i, a.0);
+                let db = self.distance(i, b.0);
+
+                if da >= 0.
+                {
+                    temp.push(a);
+                }
+
+                if (da >= 0.) != (db >= 0.)
+                {
+                    //总是从内侧的顶点往外插值，保证相邻三角形在公共边上生成同一个顶点
+                    temp.push(if da >= 0. { intersect(a, b, da, db) } else { intersect(b, a, db, da) });
+                }
+            }
+
+            std::mem::swap(polygon, temp);
+
+            if polygon.len() < 3
+            {
+                polygon.clear();
+                return;
+            }
+        }
+    }
+
+    /// 裁剪一条线段，返回false代表被完全裁剪
+    pub(super) fn clip_line<T: Varying>(&self, mask: u32, line: &mut [(Vec4, T); 2]) -> bool
+    {
+        let mut t0 = 0f32;
+        let mut t1 = 1f32;
+
+        for i in 0..PLANE_COUNT
+        {
+            if mask & (1 << i) == 0
+            {
+                continue;
+            }
+
+
+            let d0 = self.distance(i, line[0].0);
+            let d1 = self.distance(i, line[1].0);
+
+            if d0 < 0. && d1 < 0.
+            {
+                return false;
+            }
+
+            if d0 < 0.
+            {
+                t0 = t0.max(d0 / (d0 - d1));
+            }
+            else if d1 < 0.
+            {
+                t1 = t1.min(d0 / (d0 - d1));
+            }
+        }
+
+        if t0 >= t1
+        {
+            return false;
+        }
+
+        let (a, b) = (line[0], line[1]);
+
+        line[0] = (a.0.lerp(b.0, t0), a.1 * (1. - t0) + b.1 * t0);
+        line[1] = (a.0.lerp(b.0, t1), a.1 * (1. - t1) + b.1 * t1);
+
+        true
+    }
+}
+
+/// 求内侧顶点`a`到外侧顶点`b`的线段与平面的交点，`da`和`db`是它们到平面的距离
+#[inline(always)]
+fn intersect<T: Varying>(a: (Vec4, T), b: (Vec4, T), da: f32, db: f32) -> (Vec4, T)
+{
+    let t = da / (da - db);
+    (a.0.lerp(b.0, t), a.1 * (1. - t) + b.1 * t)
+}
\ No newline at end of file
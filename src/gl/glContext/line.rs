@@ -0,0 +1,157 @@
+use glam::{Vec2, Vec4};
+
+use crate::gl::{shader::{program::Program, shader::Shader, varying::Varying}, enums::glFunction::GLFunction, glFrameBuffer::GLFrameBuffer, util::is_between};
+
+use super::{GLContext, Quad};
+
+impl GLContext
+{
+    /// 光栅化一条线段。
+    ///
+    /// 没有开启LineSmooth时以变化较大的轴为主轴，主轴上的每个像素在副轴方向取以线段为中心的`line_width`个像素(DDA)，
+    /// 包含起点不包含终点，这样线带里相邻的线段不会重复绘制同一个像素。
+    ///
+    /// 开启LineSmooth时把线段当作宽为`line_width`的矩形，根据像素中心到矩形边缘的距离估算覆盖率。
+    ///
+    /// varying沿线段做透视校正插值
+    pub(super) fn line<S: Program<T> + Shader<T>, T: Varying>(&self, shader: &mut S, varying: &[T], vertices: &[Vec4], fb: &mut GLFrameBuffer)
+    {
+        let p0 = self.to_screen(vertices[0]);
+        let p1 = self.to_screen(vertices[1]);
+
+        //下面统一使用(主轴, 副轴)坐标
+        let x_major = (p1.x - p0.x).abs() >= (p1.y - p0.y).abs();
+        let to_major = |x: f32, y: f32| if x_major { Vec2::new(x, y) } else { Vec2::new(y, x) };
+
+        let start = to_major(p0.x, p0.y);
+        let delta = to_major(p1.x, p1.y) - start;
+
+        if delta.x == 0.
+        {
+            return;
+        }
+
+        let length = delta.length();
+
+        let (raster_min, raster_max) = self.raster_bounds();
+
+        let view_min = to_major(raster_min.x as f32, raster_min.y as f32).as_ivec2();
+        let view_max = to_major(raster_max.x as f32, raster_max.y as f32).as_ivec2();
+
+        let rhw0 = vertices[0].w;
+        let rhw1 = vertices[1].w;
+
+        let v0 = varying[0] * rhw0;
+        let v1 = varying[1] * rhw1;
+
+        let (z0, z1) = if self.depth_value == GLFunction::Reciprocal_W
+        {
+            (0., 0.)
+        }
+        else
+        {
+            (vertices[0].z * rhw0, vertices[1].z * rhw1)
+        };
+
+        let smooth = self.line_smooth;
+        let width = if smooth { self.line_width } else { self.line_width.round().max(1.) };
+
+        //主轴像素中心对应的线段参数
+        let param = |m: i32| (m as f32 + 0.5 - start.x) / delta.x;
+        //副轴方向上第一个像素
+        let minor = |t: f32| (start.y + t * delta.y - (width - 1.) * 0.5).floor() as i32;
+
+        //线宽导致的副轴方向上的扩展，抗锯齿时线段两端也要扩展
+        let pad = (width * 0.5).ceil() as i32 + 1;
+        let cap = if smooth { 1 } else { 0 };
+
+        let major_min = i32::max(f32::min(start.x, start.x + delta.x).floor() as i32 - cap, view_min.x);
+        let major_max = i32::min(f32::max(start.x, start.x + delta.x).floor() as i32 + cap, view_max.x);
+
+        //多重采样时线段覆盖整个像素，所有采样点使用同一个深度
+        let all_samples = (1 << fb.get_samples()) - 1;
+
+        let mut quad = Quad::default();
+        let mut coverage = [0.; 4];
+
+        let mut m = major_min;
+
+        //每次处理主轴上的两个像素，副轴方向上按2x2的像素块前进
+        while m <= major_max
+        {
+            let n0 = minor(param(m).clamp(0., 1.));
+            let n1 = minor(param(m + 1).clamp(0., 1.));
+
+            let mut n = i32::max(i32::min(n0, n1) - pad, view_min.y);
+            let n_max = i32::min(i32::max(n0, n1) + pad, view_max.y);
+
+            while n <= n_max
+            {
+                let (x, y) = if x_major { (m, n) } else { (n, m) };
+
+                quad.x = x;
+                quad.y = y;
+                quad.valid = 0;
+
+                for i in 0..4
+                {
+                    let xx = x + (i & 1);
+                    let yy = y + (i >> 1);
+                    let (pm, pn) = if x_major { (xx, yy) } else { (yy, xx) };
+
+                    let t;
+                    let inside;
+
+                    if smooth
+                    {
+                        let rel = Vec2::new(pm as f32 + 0.5, pn as f32 + 0.5) - start;
+
+                        //到起点的距离(沿线段方向)和到线段的距离(垂直方向)
+                        let along = rel.dot(delta) / length;
+                        let across = (rel.x * delta.y - rel.y * delta.x).abs() / length;
+
+                        coverage[i as usize] = (width * 0.5 + 0.5 - across).clamp(0., 1.) *
+                            (along + 0.5).clamp(0., 1.) *
+                            (length - along + 0.5).clamp(0., 1.);
+
+                        t = (along / length).clamp(0., 1.);
+                        inside = coverage[i as usize] > 0.;
+                    }
+                    else
+                    {
+                        t = param(pm);
+
+                        let first = minor(t);
+                        inside = (0. ..1.).contains(&t) && (first..first + width as i32).contains(&pn);
+                    }
+
+                    let rhw = rhw0 * (1. - t) + rhw1 * t;
+                    let w = 1. / rhw;
+
+                    quad.varyings[i as usize] = (v0 * (1. - t) + v1 * t) * w;
+
+                    if inside && is_between(pm, view_min.x, view_max.x) && is_between(pn, view_min.y, view_max.y)
+                    {
+                        let depth = if self.depth_value == GLFunction::Reciprocal_W
+                        { rhw }
+                        else
+                        { (z0 * (1. - t) + z1 * t) * w };
+
+                        self.early_test::<S, T>(&mut quad, i as usize, all_samples, |_| depth, fb);
+                    }
+                }
+
+                if quad.valid != 0
+                {
+                    quad.coverage = if smooth { Some(coverage) } else { None };
+
+                    self.shade_quad(shader, &quad, fb);
+                }
+
+                n += 2;
+            }
+
+            m += 2;
+        }
+    }
+}
\ No newline at end of file
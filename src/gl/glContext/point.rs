@@ -0,0 +1,93 @@
+use glam::{Vec2, Vec4};
+
+use crate::gl::{shader::{program::Program, shader::Shader, varying::Varying}, enums::glFunction::GLFunction, glFrameBuffer::GLFrameBuffer};
+
+use super::{GLContext, Quad};
+
+impl GLContext
+{
+    /// 光栅化一个点。
+    ///
+    /// 点是以顶点为中心、边长为点大小的正方形，像素中心落在正方形内就会被覆盖。
+    /// 开启PointSmooth时点是圆形，根据像素中心到圆心的距离估算覆盖率。
+    ///
+    /// 每个像素在点精灵内的坐标会通过Program::point_coord传给着色器
+    pub(super) fn point<S: Program<T> + Shader<T>, T: Varying>(&self, shader: &mut S, varying: &T, vertex: Vec4, fb: &mut GLFrameBuffer)
+    {
+        let center = self.to_screen(vertex);
+
+        let size = if self.program_point_size { shader.point_size(varying) } else { self.point_size };
+
+        if size <= 0.
+        {
+            return;
+        }
+
+        let smooth = self.point_smooth;
+        let size = if smooth { size } else { size.round().max(1.) };
+
+        let min = center - size * 0.5;
+        let max = center + size * 0.5;
+
+        //抗锯齿时边缘的像素也可能被部分覆盖
+        let pad = if smooth { 1 } else { 0 };
+
+        let (raster_min, raster_max) = self.raster_bounds();
+
+        let x_min = i32::max(min.x.floor() as i32 - pad, raster_min.x);
+        let y_min = i32::max(min.y.floor() as i32 - pad, raster_min.y);
+        let x_max = i32::min(max.x.floor() as i32 + pad, raster_max.x);
+        let y_max = i32::min(max.y.floor() as i32 + pad, raster_max.y);
+
+        let depth = if self.depth_value == GLFunction::Reciprocal_W { vertex.w } else { vertex.z };
+
+        //多重采样时点覆盖整个像素
+        let all_samples = (1 << fb.get_samples()) - 1;
+
+        let mut quad = Quad { varyings: [*varying; 4], ..Default::default() };
+        let mut coverage = [0.; 4];
+        let mut point_coords = [Vec2::ZERO; 4];
+
+        for y in (y_min..=y_max).step_by(2)
+        {
+            for x in (x_min..=x_max).step_by(2)
+            {
+                quad.x = x;
+                quad.y = y;
+                quad.valid = 0;
+
+                for i in 0..4
+                {
+                    let xx = x + (i & 1);
+                    let yy = y + (i >> 1);
+
+                    let pixel = Vec2::new(xx as f32 + 0.5, yy as f32 + 0.5);
+                    point_coords[i as usize] = ((pixel - min) / size).clamp(Vec2::ZERO, Vec2::ONE);
+
+                    let inside = if smooth
+                    {
+                        coverage[i as usize] = (size * 0.5 + 0.5 - pixel.distance(center)).clamp(0., 1.);
+                        coverage[i as usize] > 0.
+                    }
+                    else
+                    {
+                        pixel.cmpge(min).all() && pixel.cmplt(max).all()
+                    };
+
+                    if inside && xx <= x_max && yy <= y_max
+                    {
+                        self.early_test::<S, T>(&mut quad, i as usize, all_samples, |_| depth, fb);
+                    }
+                }
+
+                if quad.valid != 0
+                {
+                    quad.coverage = if smooth { Some(coverage) } else { None };
+                    quad.point_coords = Some(point_coords);
+
+                    self.shade_quad(shader, &quad, fb);
+                }
+            }
+        }
+    }
+}
\ No newline at end of file
@@ -1,42 +1,155 @@
-use crate::gl::glColor::GLColor;
+use std::ops::Range;
 
+use glam::{IVec2, Vec2, Vec4};
+
+use crate::gl::{glColor::GLColor, glTexture::GLTexture, enums::{glSamplePoint::GLSamplePoint, glColorFormat::GLColorFormat, glDepthFormat::GLDepthFormat, glTextureFormat::GLTextureFormat}};
+
+use self::{color::ColorBuffer, depth::DepthBuffer};
+
+mod blit;
+mod color;
+mod depth;
+
+/// 以1/16像素为单位、相对于像素中心的采样点位置，和D3D的标准采样模式一致
+macro_rules! sample_pattern {
+    ($(($x: expr, $y: expr)),*) => {
+        [$(Vec2::new(0.5 + $x as f32 / 16., 0.5 + $y as f32 / 16.)),*]
+    };
+}
+
+const PATTERN_1X: [Vec2; 1] = sample_pattern!((0, 0));
+const PATTERN_2X: [Vec2; 2] = sample_pattern!((4, 4), (-4, -4));
+const PATTERN_4X: [Vec2; 4] = sample_pattern!((-2, -6), (6, -2), (-6, 2), (2, 6));
+const PATTERN_8X: [Vec2; 8] = sample_pattern!((1, -3), (-1, 3), (5, 1), (-3, -5), (-5, 5), (-7, -1), (3, 7), (7, -7));
+const PATTERN_16X: [Vec2; 16] = sample_pattern!((1, 1), (-1, -3), (-3, 2), (4, -1), (-5, -2), (2, 5), (5, 3), (3, -5),
+    (-2, 6), (0, -7), (-4, -6), (-6, 4), (-8, 0), (7, -4), (6, 7), (-7, -8));
+
+/// 一个像素最多的采样点数量
+pub(crate) const MAX_SAMPLES: usize = 16;
+
+/// 一个帧缓冲最多的颜色附着数量
+pub const MAX_COLOR_ATTACHMENTS: usize = 8;
+
+/// 多重采样时每个像素的所有采样点连续存放。
+/// 最多有8个颜色附着，每个颜色附着可以有不同的格式，不带序号的颜色函数读写的都是第0个颜色附着
 pub struct GLFrameBuffer
 {
     width: usize,
     height: usize,
+    samples: usize,
 
-    color_buffer: Vec<GLColor>,
-    depth_buffer: Vec<f32>,
+    color_buffers: [ColorBuffer; MAX_COLOR_ATTACHMENTS],
+    depth_buffer: DepthBuffer,
     stencil_buffer: Vec<u8>,
 }
 
 impl GLFrameBuffer
 {
     pub fn new(width: usize, height: usize) -> Self
+    {
+        Self::new_multisample(width, height, GLSamplePoint::X1)
+    }
+
+    /// 创建多重采样的帧缓冲，绘制完之后要用resolve输出到单采样的帧缓冲
+    pub fn new_multisample(width: usize, height: usize, samples: GLSamplePoint) -> Self
     {
         return Self {
             width,
             height,
+            samples: samples as usize,
 
-            color_buffer: Vec::new(),
-            depth_buffer: Vec::new(),
+            color_buffers: Default::default(),
+            depth_buffer: DepthBuffer::default(),
             stencil_buffer: Vec::new(),
         }
     }
 
     pub fn attach_color(&mut self)
     {
-        self.color_buffer.resize(self.width * self.height, GLColor::ONE);
+        self.attach_color_at(0);
+    }
+
+    /// 添加第index个颜色附着，片段着色器输出的第index个颜色会写入这里
+    pub fn attach_color_at(&mut self, index: usize)
+    {
+        self.attach_color_format(index, GLColorFormat::RGBA8);
+    }
+
+    /// 添加指定格式的颜色附着，已经存在的颜色附着会被替换
+    pub fn attach_color_format(&mut self, index: usize, format: GLColorFormat)
+    {
+        if index >= MAX_COLOR_ATTACHMENTS
+        {
+            eprintln!("无效的颜色附着序号");
+            return;
+        }
+
+        self.color_buffers[index] = ColorBuffer::new(format, self.width * self.height * self.samples);
+    }
+
+    pub fn get_color_format(&self, index: usize) -> GLColorFormat
+    {
+        self.color_buffers[index].format()
+    }
+
+    pub fn is_color_attached(&self, index: usize) -> bool
+    {
+        index < MAX_COLOR_ATTACHMENTS && !self.color_buffers[index].is_empty()
+    }
+
+    /// 第i位为1代表添加了第i个颜色附着
+    pub(crate) fn color_attachment_mask(&self) -> u32
+    {
+        let mut mask = 0;
+
+        for (i, buffer) in self.color_buffers.iter().enumerate()
+        {
+            if !buffer.is_empty()
+            {
+                mask |= 1 << i;
+            }
+        }
+
+        mask
     }
 
     pub fn attach_depth(&mut self)
     {
-        self.depth_buffer.resize(self.width * self.height, 1.0);
+        self.attach_depth_format(GLDepthFormat::D32F);
     }
 
+    /// 添加指定格式的深度附着，D24S8格式会同时包含模板，不再需要单独的模板附着
+    pub fn attach_depth_format(&mut self, format: GLDepthFormat)
+    {
+        self.depth_buffer = DepthBuffer::new(format, self.width * self.height * self.samples);
+
+        if format.has_stencil()
+        {
+            self.stencil_buffer = Vec::new();
+        }
+    }
+
+    pub fn get_depth_format(&self) -> GLDepthFormat
+    {
+        self.depth_buffer.format()
+    }
+
+    /// 深度附着是D24S8格式时什么也不做
     pub fn attach_stencil(&mut self)
     {
-        self.stencil_buffer.resize(self.width * self.height, 0x00);
+        if self.packed_stencil()
+        {
+            return;
+        }
+
+        self.stencil_buffer.resize(self.width * self.height * self.samples, 0x00);
+    }
+
+    /// 模板是否存放在D24S8格式的深度附着中
+    #[inline(always)]
+    fn packed_stencil(&self) -> bool
+    {
+        self.depth_buffer.format().has_stencil() && !self.depth_buffer.is_empty()
     }
 
     pub fn attach_all(&mut self)
@@ -56,139 +169,400 @@ impl GLFrameBuffer
         self.height
     }
 
-    pub fn get_color_buffer(&self) -> &[u8]
+    pub fn get_samples(&self) -> usize
     {
-        unsafe
+        self.samples
+    }
+
+    /// 采样点在像素内的位置，范围是[0, 1)
+    pub fn get_sample_positions(&self) -> &'static [Vec2]
+    {
+        match self.samples
         {
-            &*std::ptr::slice_from_raw_parts(self.color_buffer.as_ptr() as *const u8, 
-            self.color_buffer.len() * 4)
+            2 => &PATTERN_2X,
+            4 => &PATTERN_4X,
+            8 => &PATTERN_8X,
+            16 => &PATTERN_16X,
+            _ => &PATTERN_1X,
         }
     }
 
-    pub fn get_depth_buffer(&self) -> &[f32]
+    /// 多重采样时返回的是所有采样点的数据
+    pub fn get_color_buffer(&self) -> &[u8]
+    {
+        self.get_color_buffer_at(0)
+    }
+
+    /// 按照颜色附着的格式排列的原始数据，RGBA16F每个通道是一个半精度浮点数
+    pub fn get_color_buffer_at(&self, index: usize) -> &[u8]
+    {
+        self.color_buffers[index].as_bytes()
+    }
+
+    /// 按照深度附着的格式排列的原始数据，D32F格式时每4个字节是一个f32
+    pub fn get_depth_buffer(&self) -> &[u8]
     {
-        &self.depth_buffer
+        self.depth_buffer.as_bytes()
     }
 
+    /// 单独的模板附着，D24S8格式的模板在深度附着的数据里
     pub fn get_stencil_buffer(&self) -> &[u8]
     {
         &self.stencil_buffer
     }
 
+    /// 取出第0个颜色附着，不是RGBA8格式时会先转换，之后重新添加一个同样格式的颜色附着
     pub fn take_color_buffer(&mut self) -> Vec<GLColor>
     {
-        let result = std::mem::take(&mut self.color_buffer);
-        self.attach_color();
+        let format = self.color_buffers[0].format();
+        let result = self.color_buffers[0].take_colors();
+        self.attach_color_format(0, format);
 
         result
     }
 
-    #[unchecked::unchecked]
+    /// 把第index个颜色附着转换为同样格式的纹理，之后这个颜色附着不再存在，可以用attach_color_texture放回来。
+    /// RGBA8格式不会复制数据，其它格式会复制一次。多重采样的帧缓冲要先resolve到单采样的帧缓冲
+    pub fn take_color_texture(&mut self, index: usize) -> Option<GLTexture>
+    {
+        if !self.is_color_attached(index)
+        {
+            eprintln!("颜色附着不存在");
+            return None;
+        }
+
+        if self.samples != 1
+        {
+            eprintln!("多重采样的帧缓冲不能转换为纹理");
+            return None;
+        }
+
+        //先检查大小，避免转换失败时颜色附着已经被取走
+        let format = texture_format(self.color_buffers[index].format());
+
+        if self.color_buffers[index].as_bytes().len() != format.data_size(self.width, self.height)
+        {
+            eprintln!("颜色附着的大小和帧缓冲不同");
+            return None;
+        }
+
+        let mut buffer = std::mem::take(&mut self.color_buffers[index]);
+
+        match buffer.format()
+        {
+            GLColorFormat::RGBA8 => GLTexture::from_colors(buffer.take_colors(), self.width, self.height),
+            _ => GLTexture::from_data(buffer.as_bytes().to_vec(), self.width, self.height, format),
+        }
+    }
+
+    /// 把纹理作为第index个颜色附着，纹理的长宽必须和帧缓冲相同，纹理的mipmap会被丢弃。
+    /// RGBA8格式不会复制数据，RGB8、深度和整数格式的纹理不能作为颜色附着
+    pub fn attach_color_texture(&mut self, index: usize, texture: GLTexture) -> bool
+    {
+        if index >= MAX_COLOR_ATTACHMENTS
+        {
+            eprintln!("无效的颜色附着序号");
+            return false;
+        }
+
+        if self.samples != 1 || texture.get_width() != self.width || texture.get_height() != self.height
+        {
+            eprintln!("纹理和帧缓冲的大小不同");
+            return false;
+        }
+
+        self.color_buffers[index] = match color_format(texture.get_format())
+        {
+            Some(GLColorFormat::RGBA8) => ColorBuffer::from_colors(texture.into_colors()),
+            Some(format) => ColorBuffer::from_bytes(format, &texture.into_data()),
+            None =>
+            {
+                eprintln!("纹理的格式不能作为颜色附着");
+                return false;
+            }
+        };
+
+        true
+    }
+
+    /// 把深度附着复制到D32F格式的纹理中，给阴影贴图使用，深度附着保持不变。
+    /// 采样结果的x是深度，可以直接和片段的深度比较
+    pub fn get_depth_texture(&self) -> Option<GLTexture>
+    {
+        if self.depth_buffer.is_empty() || self.samples != 1
+        {
+            eprintln!("深度附着不存在或者是多重采样的");
+            return None;
+        }
+
+        let data = (0..self.depth_buffer.len()).flat_map(|i| self.depth_buffer.get(i).to_ne_bytes()).collect();
+
+        GLTexture::from_data(data, self.width, self.height, GLTextureFormat::D32F)
+    }
+
+    /// 多重采样时读写的是第0个采样点
     pub fn get_color(&self, x: i32, y: i32) -> GLColor
     {
-        self.color_buffer[y as usize * self.width + x as usize]
+        self.get_sample_color(x, y, 0)
     }
 
-    #[unchecked::unchecked]
     pub fn get_depth(&self, x: i32, y: i32) -> f32
     {
-        self.depth_buffer[y as usize * self.width + x as usize]
+        self.get_sample_depth(x, y, 0)
     }
 
-    #[unchecked::unchecked]
     pub fn get_stencil(&self, x: i32, y: i32) -> u8
     {
-        self.stencil_buffer[y as usize * self.width + x as usize]
+        self.get_sample_stencil(x, y, 0)
     }
 
-    #[unchecked::unchecked]
     pub fn set_color(&mut self, x: i32, y: i32, color: GLColor)
     {
-        self.color_buffer[y as usize * self.width + x as usize] = color;
+        self.set_sample_color(x, y, 0, color);
     }
 
-    #[unchecked::unchecked]
     pub fn set_depth(&mut self, x: i32, y: i32, depth: f32)
     {
-        self.depth_buffer[y as usize * self.width + x as usize] = depth;
+        self.set_sample_depth(x, y, 0, depth);
     }
 
-    #[unchecked::unchecked]
     pub fn set_stencil(&mut self, x: i32, y: i32, stencil: u8)
     {
-        self.stencil_buffer[y as usize * self.width + x as usize] = stencil;
+        self.set_sample_stencil(x, y, 0, stencil);
     }
 
-    pub fn set_color_buffer(&mut self, buffer: Vec<GLColor>)
+    pub fn get_sample_color(&self, x: i32, y: i32, sample: usize) -> GLColor
     {
-        self.color_buffer = buffer;
+        self.get_sample_color_at(0, x, y, sample)
     }
 
-    pub(crate) fn do_clear_color(&mut self, color: GLColor)
+    /// 不是RGBA8格式时会转换成GLColor
+    #[unchecked::unchecked]
+    pub fn get_sample_color_at(&self, index: usize, x: i32, y: i32, sample: usize) -> GLColor
+    {
+        self.color_buffers[index].get_color((y as usize * self.width + x as usize) * self.samples + sample)
+    }
+
+    /// 按照浮点数读取，浮点格式的值不会被限制在[0, 1]内
+    #[unchecked::unchecked]
+    pub fn get_sample_color_f_at(&self, index: usize, x: i32, y: i32, sample: usize) -> Vec4
+    {
+        self.color_buffers[index].get((y as usize * self.width + x as usize) * self.samples + sample)
+    }
+
+    #[unchecked::unchecked]
+    pub fn get_sample_depth(&self, x: i32, y: i32, sample: usize) -> f32
+    {
+        self.depth_buffer.get((y as usize * self.width + x as usize) * self.samples + sample)
+    }
+
+    #[unchecked::unchecked]
+    pub fn get_sample_stencil(&self, x: i32, y: i32, sample: usize) -> u8
+    {
+        let i = (y as usize * self.width + x as usize) * self.samples + sample;
+        if self.packed_stencil() { self.depth_buffer.get_stencil(i) } else { self.stencil_buffer[i] }
+    }
+
+    pub fn set_sample_color(&mut self, x: i32, y: i32, sample: usize, color: GLColor)
+    {
+        self.set_sample_color_at(0, x, y, sample, color);
+    }
+
+    #[unchecked::unchecked]
+    pub fn set_sample_color_at(&mut self, index: usize, x: i32, y: i32, sample: usize, color: GLColor)
+    {
+        self.color_buffers[index].set_color((y as usize * self.width + x as usize) * self.samples + sample, color);
+    }
+
+    #[unchecked::unchecked]
+    pub fn set_sample_color_f_at(&mut self, index: usize, x: i32, y: i32, sample: usize, color: Vec4)
+    {
+        self.color_buffers[index].set((y as usize * self.width + x as usize) * self.samples + sample, color);
+    }
+
+    /// 定点格式的深度会被限制在[0, 1]内并量化
+    #[unchecked::unchecked]
+    pub fn set_sample_depth(&mut self, x: i32, y: i32, sample: usize, depth: f32)
+    {
+        self.depth_buffer.set((y as usize * self.width + x as usize) * self.samples + sample, depth);
+    }
+
+    #[unchecked::unchecked]
+    pub fn set_sample_stencil(&mut self, x: i32, y: i32, sample: usize, stencil: u8)
+    {
+        let i = (y as usize * self.width + x as usize) * self.samples + sample;
+        if self.packed_stencil() { self.depth_buffer.set_stencil(i, stencil) } else { self.stencil_buffer[i] = stencil }
+    }
+
+    /// 深度按照深度附着的格式写入再读出的值，深度测试时用它和缓冲中的值比较
+    #[inline(always)]
+    pub(crate) fn quantize_depth(&self, depth: f32) -> f32
+    {
+        self.depth_buffer.quantize(depth)
+    }
+
+    /// 把多重采样的帧缓冲输出到大小相同的单采样帧缓冲，每个颜色附着取所有采样点的平均值，
+    /// 深度和模板取第0个采样点
+    pub fn resolve(&self, dst: &mut GLFrameBuffer)
     {
-        if color.r == color.g &&
-        color.r == color.b &&
-        color.r == color.a
+        if dst.width != self.width || dst.height != self.height || dst.samples != 1
+        {
+            eprintln!("resolve的目标帧缓冲大小不一致或者不是单采样的");
+            return;
+        }
+
+        for (dst_buffer, src_buffer) in dst.color_buffers.iter_mut().zip(self.color_buffers.iter())
         {
-            unsafe
+            if dst_buffer.is_empty() || src_buffer.is_empty()
             {
-                std::ptr::write_bytes(self.color_buffer.as_mut_ptr(), color.r,
-                    self.color_buffer.len());
+                continue;
             }
+
+            src_buffer.resolve(dst_buffer, self.samples);
         }
-        else
-        {
-            let ptr = self.color_buffer.as_mut_ptr() as *mut u32;
-            let end_idx = self.color_buffer.len();
-            let mut current_idx = 4;
 
-            unsafe
+        if !dst.depth_buffer.is_empty() && !self.depth_buffer.is_empty()
+        {
+            for i in 0..dst.depth_buffer.len()
             {
-                *ptr = color.into();
-
-                while current_idx << 1 <= end_idx
-                {
-                    self.color_buffer.copy_within(0..current_idx, current_idx);
-                    current_idx <<= 1;
-                }
+                dst.depth_buffer.set(i, self.depth_buffer.get(i * self.samples));
+            }
+        }
 
-                if current_idx != end_idx
+        if (!dst.stencil_buffer.is_empty() || dst.packed_stencil()) && (!self.stencil_buffer.is_empty() || self.packed_stencil())
+        {
+            for y in 0..self.height as i32
+            {
+                for x in 0..self.width as i32
                 {
-                    self.color_buffer.copy_within(0..(end_idx - current_idx), current_idx);
+                    dst.set_stencil(x, y, self.get_stencil(x, y));
                 }
             }
         }
     }
 
+    /// 第0个颜色附着会变为RGBA8格式
+    pub fn set_color_buffer(&mut self, buffer: Vec<GLColor>)
+    {
+        self.color_buffers[0] = ColorBuffer::from_colors(buffer);
+    }
+
+    /// 清除所有颜色附着，RGBA8格式使用color，其它格式使用value
+    pub(crate) fn do_clear_color(&mut self, color: GLColor, value: Vec4)
+    {
+        for buffer in self.color_buffers.iter_mut().filter(|buffer| !buffer.is_empty())
+        {
+            buffer.clear(color, value);
+        }
+    }
+
+    /// D24S8格式只清除深度，模板不变
     pub(crate) fn do_clear_depth(&mut self, depth: f32)
     {
-        let ptr = self.depth_buffer.as_mut_ptr() as *mut f32;
-        let end_idx = self.depth_buffer.len();
-        let mut current_idx = 1;
+        let len = self.depth_buffer.len();
+        self.depth_buffer.fill(0, len, depth);
+    }
+
+    pub(crate) fn do_clear_stencil(&mut self, stencil: u8)
+    {
+        if self.packed_stencil()
+        {
+            let len = self.depth_buffer.len();
+            self.depth_buffer.fill_stencil(0, len, stencil);
+            return;
+        }
 
         unsafe
         {
-            *ptr = depth;
+            std::ptr::write_bytes(self.stencil_buffer.as_mut_ptr(), stencil,
+                self.stencil_buffer.len());
+        }
+    }
 
-            while current_idx << 1 <= end_idx
+    /// 只清除[min, max]范围内的颜色，给剪裁测试用
+    pub(crate) fn do_clear_color_rect(&mut self, color: GLColor, value: Vec4, min: IVec2, max: IVec2)
+    {
+        for buffer in self.color_buffers.iter_mut().filter(|buffer| !buffer.is_empty())
+        {
+            for row in rect_rows(self.width, self.samples, min, max)
             {
-                self.depth_buffer.copy_within(0..current_idx, current_idx);
-                current_idx <<= 1;
+                buffer.fill(row.start, row.end, color, value);
             }
+        }
+    }
 
-            if current_idx != end_idx
-            {
-                self.depth_buffer.copy_within(0..(end_idx - current_idx), current_idx);
-            }
+    pub(crate) fn do_clear_depth_rect(&mut self, depth: f32, min: IVec2, max: IVec2)
+    {
+        if self.depth_buffer.is_empty()
+        {
+            return;
+        }
+
+        for row in rect_rows(self.width, self.samples, min, max)
+        {
+            self.depth_buffer.fill(row.start, row.end, depth);
         }
     }
 
-    pub(crate) fn do_clear_stencil(&mut self, stencil: u8)
+    pub(crate) fn do_clear_stencil_rect(&mut self, stencil: u8, min: IVec2, max: IVec2)
     {
-        unsafe
+        let packed = self.packed_stencil();
+
+        if !packed && self.stencil_buffer.is_empty()
         {
-            std::ptr::write_bytes(self.stencil_buffer.as_mut_ptr(), stencil,
-                self.stencil_buffer.len());
+            return;
         }
+
+        for row in rect_rows(self.width, self.samples, min, max)
+        {
+            if packed
+            {
+                self.depth_buffer.fill_stencil(row.start, row.end, stencil);
+            }
+            else
+            {
+                self.stencil_buffer[row].fill(stencil);
+            }
+        }
+    }
+}
+
+/// 矩形区域每一行在缓冲中的下标范围
+fn rect_rows(width: usize, samples: usize, min: IVec2, max: IVec2) -> impl Iterator<Item = Range<usize>>
+{
+    (min.y..=max.y).map(move |y|
+    {
+        let row = y as usize * width;
+        (row + min.x as usize) * samples..(row + max.x as usize + 1) * samples
+    })
+}
+
+/// 颜色附着的格式对应的纹理格式
+fn texture_format(format: GLColorFormat) -> GLTextureFormat
+{
+    match format
+    {
+        GLColorFormat::R8 => GLTextureFormat::R8,
+        GLColorFormat::RG8 => GLTextureFormat::RG8,
+        GLColorFormat::RGBA8 => GLTextureFormat::RGBA8,
+        GLColorFormat::RGBA16F => GLTextureFormat::RGBA16F,
+        GLColorFormat::R32F => GLTextureFormat::R32F,
+        GLColorFormat::RGBA32F => GLTextureFormat::RGBA32F,
+        GLColorFormat::R11G11B10F => GLTextureFormat::R11G11B10F,
+    }
+}
+
+fn color_format(format: GLTextureFormat) -> Option<GLColorFormat>
+{
+    match format
+    {
+        GLTextureFormat::R8 => Some(GLColorFormat::R8),
+        GLTextureFormat::RG8 => Some(GLColorFormat::RG8),
+        GLTextureFormat::RGBA8 => Some(GLColorFormat::RGBA8),
+        GLTextureFormat::RGBA16F => Some(GLColorFormat::RGBA16F),
+        GLTextureFormat::R32F => Some(GLColorFormat::R32F),
+        GLTextureFormat::RGBA32F => Some(GLColorFormat::RGBA32F),
+        GLTextureFormat::R11G11B10F => Some(GLColorFormat::R11G11B10F),
+        _ => None,
     }
 }
\ No newline at end of file
@@ -0,0 +1,142 @@
+use glam::{IVec2, IVec4, Vec2, Vec4};
+
+use crate::gl::enums::glBufferBit::GLBufferBit;
+
+use super::{GLFrameBuffer, MAX_COLOR_ATTACHMENTS};
+
+/// 一个轴上目标像素到源坐标的映射，start和end可以反过来表示翻转
+#[derive(Clone, Copy)]
+struct BlitAxis
+{
+    dst_start: f32,
+    src_start: f32,
+    scale: f32,
+
+    /// 源矩形在这个轴上和帧缓冲相交的像素范围，两端都包含
+    min: i32,
+    max: i32,
+}
+
+impl BlitAxis
+{
+    fn new(src_start: i32, src_end: i32, dst_start: i32, dst_end: i32, size: usize) -> Self
+    {
+        Self {
+            dst_start: dst_start as f32,
+            src_start: src_start as f32,
+            scale: (src_end - src_start) as f32 / (dst_end - dst_start) as f32,
+
+            min: i32::max(i32::min(src_start, src_end), 0),
+            max: i32::min(i32::max(src_start, src_end), size as i32) - 1,
+        }
+    }
+
+    /// 目标像素中心对应的源坐标
+    fn map(&self, dst: i32) -> f32
+    {
+        self.src_start + (dst as f32 + 0.5 - self.dst_start) * self.scale
+    }
+
+    fn clamp(&self, src: i32) -> i32
+    {
+        src.clamp(self.min, self.max)
+    }
+}
+
+impl GLFrameBuffer
+{
+    fn has_stencil(&self) -> bool
+    {
+        !self.stencil_buffer.is_empty() || self.packed_stencil()
+    }
+
+    /// 多重采样时返回所有采样点的平均值
+    fn get_pixel_color_f_at(&self, index: usize, x: i32, y: i32) -> Vec4
+    {
+        let mut sum = Vec4::ZERO;
+
+        for sample in 0..self.samples
+        {
+            sum += self.get_sample_color_f_at(index, x, y, sample);
+        }
+
+        sum / self.samples as f32
+    }
+
+    fn linear_color(&self, index: usize, st: Vec2, x: &BlitAxis, y: &BlitAxis) -> Vec4
+    {
+        let st = st - 0.5;
+        let base = st.floor();
+        let frac = st - base;
+
+        let (x0, y0) = (x.clamp(base.x as i32), y.clamp(base.y as i32));
+        let (x1, y1) = (x.clamp(base.x as i32 + 1), y.clamp(base.y as i32 + 1));
+
+        let top = self.get_pixel_color_f_at(index, x0, y0).lerp(self.get_pixel_color_f_at(index, x1, y0), frac.x);
+        let bottom = self.get_pixel_color_f_at(index, x0, y1).lerp(self.get_pixel_color_f_at(index, x1, y1), frac.x);
+
+        top.lerp(bottom, frac.y)
+    }
+
+    /// 把src的src_rect复制到自己的dst_rect，只写入[min, max]以内的像素。
+    /// 第i个颜色附着复制到第i个颜色附着，多重采样的源取所有采样点的平均值，深度和模板取第0个采样点
+    pub(crate) fn do_blit(&mut self, src: &GLFrameBuffer, src_rect: IVec4, dst_rect: IVec4, (min, max): (IVec2, IVec2), mask: GLBufferBit, linear: bool)
+    {
+        let color = mask.contains(GLBufferBit::Color);
+        let depth = mask.contains(GLBufferBit::Depth);
+        let stencil = mask.contains(GLBufferBit::Stencil);
+
+        let x_axis = BlitAxis::new(src_rect.x, src_rect.z, dst_rect.x, dst_rect.z, src.width);
+        let y_axis = BlitAxis::new(src_rect.y, src_rect.w, dst_rect.y, dst_rect.w, src.height);
+
+        if x_axis.min > x_axis.max || y_axis.min > y_axis.max
+        {
+            return;
+        }
+
+        let color_indices: Vec<usize> = (0..MAX_COLOR_ATTACHMENTS)
+            .filter(|&i| color && self.is_color_attached(i) && src.is_color_attached(i))
+            .collect();
+        let depth = depth && !self.depth_buffer.is_empty() && !src.depth_buffer.is_empty();
+        let stencil = stencil && self.has_stencil() && src.has_stencil();
+
+        for dy in min.y..=max.y
+        {
+            for dx in min.x..=max.x
+            {
+                let st = Vec2::new(x_axis.map(dx), y_axis.map(dy));
+                let (sx, sy) = (x_axis.clamp(st.x.floor() as i32), y_axis.clamp(st.y.floor() as i32));
+
+                for &index in &color_indices
+                {
+                    let value = if linear { src.linear_color(index, st, &x_axis, &y_axis) } else { src.get_pixel_color_f_at(index, sx, sy) };
+
+                    for sample in 0..self.samples
+                    {
+                        self.set_sample_color_f_at(index, dx, dy, sample, value);
+                    }
+                }
+
+                if depth
+                {
+                    let value = src.get_sample_depth(sx, sy, 0);
+
+                    for sample in 0..self.samples
+                    {
+                        self.set_sample_depth(dx, dy, sample, value);
+                    }
+                }
+
+                if stencil
+                {
+                    let value = src.get_sample_stencil(sx, sy, 0);
+
+                    for sample in 0..self.samples
+                    {
+                        self.set_sample_stencil(dx, dy, sample, value);
+                    }
+                }
+            }
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,305 @@
+use glam::Vec4;
+
+use crate::gl::{glColor::GLColor, enums::glColorFormat::GLColorFormat, util::{f32_to_f16, f16_to_f32, pack_r11g11b10f, unpack_r11g11b10f}};
+
+/// 按照格式存放的像素数据，RGBA8直接存放GLColor，其它格式每个通道一个元素
+enum ColorData
+{
+    Rgba8(Vec<GLColor>),
+    U8(Vec<u8>),
+    U16(Vec<u16>),
+    F32(Vec<f32>),
+    U32(Vec<u32>),
+}
+
+/// 一个颜色附着，下标是采样点的序号。
+/// 缺少的通道读出时g、b为0，a为1
+pub(super) struct ColorBuffer
+{
+    format: GLColorFormat,
+    data: ColorData,
+}
+
+impl Default for ColorBuffer
+{
+    fn default() -> Self
+    {
+        Self { format: GLColorFormat::RGBA8, data: ColorData::Rgba8(Vec::new()) }
+    }
+}
+
+impl ColorBuffer
+{
+    /// 分配len个采样点，初始值为白色
+    pub(super) fn new(format: GLColorFormat, len: usize) -> Self
+    {
+        let data = match format
+        {
+            GLColorFormat::RGBA8 => ColorData::Rgba8(vec![GLColor::ONE; len]),
+            GLColorFormat::R8 => ColorData::U8(vec![0xFF; len]),
+            GLColorFormat::RG8 => ColorData::U8(vec![0xFF; len * 2]),
+            GLColorFormat::RGBA16F => ColorData::U16(vec![f32_to_f16(1.); len * 4]),
+            GLColorFormat::R32F => ColorData::F32(vec![1.; len]),
+            GLColorFormat::RGBA32F => ColorData::F32(vec![1.; len * 4]),
+            GLColorFormat::R11G11B10F => ColorData::U32(vec![pack_r11g11b10f(1., 1., 1.); len]),
+        };
+
+        Self { format, data }
+    }
+
+    pub(super) fn format(&self) -> GLColorFormat
+    {
+        self.format
+    }
+
+    pub(super) fn is_empty(&self) -> bool
+    {
+        self.as_bytes().is_empty()
+    }
+
+    pub(super) fn as_bytes(&self) -> &[u8]
+    {
+        fn bytes<T>(data: &[T]) -> &[u8]
+        {
+            unsafe
+            {
+                &*std::ptr::slice_from_raw_parts(data.as_ptr() as *const u8, std::mem::size_of_val(data))
+            }
+        }
+
+        match &self.data
+        {
+            ColorData::Rgba8(data) => bytes(data),
+            ColorData::U8(data) => bytes(data),
+            ColorData::U16(data) => bytes(data),
+            ColorData::F32(data) => bytes(data),
+            ColorData::U32(data) => bytes(data),
+        }
+    }
+
+    /// 取出RGBA8格式的数据，其它格式会先转换
+    pub(super) fn take_colors(&mut self) -> Vec<GLColor>
+    {
+        match &mut self.data
+        {
+            ColorData::Rgba8(data) => std::mem::take(data),
+            _ =>
+            {
+                let len = self.as_bytes().len() / self.format.size();
+                let result = (0..len).map(|i| self.get_color(i)).collect();
+                *self = Self::default();
+
+                result
+            }
+        }
+    }
+
+    pub(super) fn from_colors(colors: Vec<GLColor>) -> Self
+    {
+        Self { format: GLColorFormat::RGBA8, data: ColorData::Rgba8(colors) }
+    }
+
+    /// 从按照格式紧密排列的原始数据创建，RGBA8以外的格式会复制
+    pub(super) fn from_bytes(format: GLColorFormat, bytes: &[u8]) -> Self
+    {
+        let data = match format
+        {
+            GLColorFormat::RGBA8 => ColorData::Rgba8(bytes.chunks_exact(4).map(|c| GLColor::new(c[0], c[1], c[2], c[3])).collect()),
+            GLColorFormat::R8 | GLColorFormat::RG8 => ColorData::U8(bytes.to_vec()),
+            GLColorFormat::RGBA16F => ColorData::U16(bytes.chunks_exact(2).map(|c| u16::from_ne_bytes([c[0], c[1]])).collect()),
+            GLColorFormat::R32F | GLColorFormat::RGBA32F => ColorData::F32(bytes.chunks_exact(4).map(|c| f32::from_ne_bytes([c[0], c[1], c[2], c[3]])).collect()),
+            GLColorFormat::R11G11B10F => ColorData::U32(bytes.chunks_exact(4).map(|c| u32::from_ne_bytes([c[0], c[1], c[2], c[3]])).collect()),
+        };
+
+        Self { format, data }
+    }
+
+    /// RGBA8以外的格式会转换成GLColor，浮点数会被限制在[0, 1]内
+    #[unchecked::unchecked]
+    #[inline(always)]
+    pub(super) fn get_color(&self, i: usize) -> GLColor
+    {
+        match &self.data
+        {
+            ColorData::Rgba8(data) => data[i],
+            ColorData::U8(data) if self.format == GLColorFormat::R8 => GLColor::new(data[i], 0, 0, 255),
+            ColorData::U8(data) => GLColor::new(data[i * 2], data[i * 2 + 1], 0, 255),
+            _ => unorm_to_color(self.get(i)),
+        }
+    }
+
+    #[unchecked::unchecked]
+    #[inline(always)]
+    pub(super) fn set_color(&mut self, i: usize, color: GLColor)
+    {
+        match &mut self.data
+        {
+            ColorData::Rgba8(data) => data[i] = color,
+            ColorData::U8(data) if self.format == GLColorFormat::R8 => data[i] = color.r,
+            ColorData::U8(data) =>
+            {
+                data[i * 2] = color.r;
+                data[i * 2 + 1] = color.g;
+            }
+            _ => self.set(i, color.into()),
+        }
+    }
+
+    #[unchecked::unchecked]
+    pub(super) fn get(&self, i: usize) -> Vec4
+    {
+        match &self.data
+        {
+            ColorData::Rgba8(data) => data[i].into(),
+            ColorData::U8(_) => self.get_color(i).into(),
+            ColorData::U16(data) => Vec4::new(f16_to_f32(data[i * 4]), f16_to_f32(data[i * 4 + 1]), f16_to_f32(data[i * 4 + 2]), f16_to_f32(data[i * 4 + 3])),
+            ColorData::F32(data) if self.format == GLColorFormat::R32F => Vec4::new(data[i], 0., 0., 1.),
+            ColorData::F32(data) => Vec4::new(data[i * 4], data[i * 4 + 1], data[i * 4 + 2], data[i * 4 + 3]),
+            ColorData::U32(data) =>
+            {
+                let (r, g, b) = unpack_r11g11b10f(data[i]);
+                Vec4::new(r, g, b, 1.)
+            }
+        }
+    }
+
+    /// 定点格式会先限制在[0, 1]内再四舍五入
+    #[unchecked::unchecked]
+    pub(super) fn set(&mut self, i: usize, value: Vec4)
+    {
+        match &mut self.data
+        {
+            ColorData::Rgba8(_) | ColorData::U8(_) => self.set_color(i, unorm_to_color(value)),
+            ColorData::U16(data) =>
+            {
+                for c in 0..4
+                {
+                    data[i * 4 + c] = f32_to_f16(value[c]);
+                }
+            }
+            ColorData::F32(data) if self.format == GLColorFormat::R32F => data[i] = value.x,
+            ColorData::F32(data) => data[i * 4..i * 4 + 4].copy_from_slice(&value.to_array()),
+            ColorData::U32(data) => data[i] = pack_r11g11b10f(value.x, value.y, value.z),
+        }
+    }
+
+    /// 把[start, end)范围内的采样点设为同一个值，RGBA8使用color，其它格式使用value
+    pub(super) fn fill(&mut self, start: usize, end: usize, color: GLColor, value: Vec4)
+    {
+        fn fill_pixels<T: Copy>(data: &mut [T], pixel: &[T], start: usize, end: usize)
+        {
+            let n = pixel.len();
+
+            for chunk in data[start * n..end * n].chunks_exact_mut(n)
+            {
+                chunk.copy_from_slice(pixel);
+            }
+        }
+
+        let unorm = unorm_to_color(value);
+
+        match &mut self.data
+        {
+            ColorData::Rgba8(data) => data[start..end].fill(color),
+            ColorData::U8(data) if self.format == GLColorFormat::R8 => data[start..end].fill(unorm.r),
+            ColorData::U8(data) => fill_pixels(data, &[unorm.r, unorm.g], start, end),
+            ColorData::U16(data) => fill_pixels(data, &value.to_array().map(f32_to_f16), start, end),
+            ColorData::F32(data) if self.format == GLColorFormat::R32F => data[start..end].fill(value.x),
+            ColorData::F32(data) => fill_pixels(data, &value.to_array(), start, end),
+            ColorData::U32(data) => data[start..end].fill(pack_r11g11b10f(value.x, value.y, value.z)),
+        }
+    }
+
+    /// 每samples个采样点取平均值写入dst的一个像素，RGBA8之间使用整数计算，其它格式按照浮点数计算
+    pub(super) fn resolve(&self, dst: &mut ColorBuffer, samples: usize)
+    {
+        if let (ColorData::Rgba8(src), ColorData::Rgba8(dst)) = (&self.data, &mut dst.data)
+        {
+            for (pixel, samples) in dst.iter_mut().zip(src.chunks_exact(samples))
+            {
+                let mut sum = [0u32; 4];
+
+                for color in samples
+                {
+                    sum[0] += color.r as u32;
+                    sum[1] += color.g as u32;
+                    sum[2] += color.b as u32;
+                    sum[3] += color.a as u32;
+                }
+
+                let half = samples.len() as u32 / 2;
+                let n = samples.len() as u32;
+
+                *pixel = GLColor { r: ((sum[0] + half) / n) as u8, g: ((sum[1] + half) / n) as u8, b: ((sum[2] + half) / n) as u8, a: ((sum[3] + half) / n) as u8 };
+            }
+
+            return;
+        }
+
+        let len = dst.as_bytes().len() / dst.format.size();
+
+        for i in 0..len
+        {
+            let sum = (0..samples).fold(Vec4::ZERO, |sum, s| sum + self.get(i * samples + s));
+            dst.set(i, sum / samples as f32);
+        }
+    }
+
+    /// 清除整个颜色附着
+    pub(super) fn clear(&mut self, color: GLColor, value: Vec4)
+    {
+        match &mut self.data
+        {
+            ColorData::Rgba8(data) => clear_color_buffer(data, color),
+            _ =>
+            {
+                let len = self.as_bytes().len() / self.format.size();
+                self.fill(0, len, color, value);
+            }
+        }
+    }
+}
+
+/// 限制在[0, 1]内之后四舍五入转换为GLColor
+#[inline(always)]
+fn unorm_to_color(value: Vec4) -> GLColor
+{
+    let value = value.clamp(Vec4::ZERO, Vec4::ONE) * 255. + 0.5;
+    GLColor::new(value.x as u8, value.y as u8, value.z as u8, value.w as u8)
+}
+
+fn clear_color_buffer(buffer: &mut [GLColor], color: GLColor)
+{
+    if color.r == color.g &&
+    color.r == color.b &&
+    color.r == color.a
+    {
+        unsafe
+        {
+            std::ptr::write_bytes(buffer.as_mut_ptr(), color.r,
+                buffer.len());
+        }
+    }
+    else
+    {
+        let ptr = buffer.as_mut_ptr() as *mut u32;
+        let end_idx = buffer.len();
+        let mut current_idx = 1;
+
+        unsafe
+        {
+            *ptr = color.into();
+
+            while current_idx << 1 <= end_idx
+            {
+                buffer.copy_within(0..current_idx, current_idx);
+                current_idx <<= 1;
+            }
+
+            if current_idx != end_idx
+            {
+                buffer.copy_within(0..(end_idx - current_idx), current_idx);
+            }
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,183 @@
+use crate::gl::enums::glDepthFormat::GLDepthFormat;
+
+const D16_MAX: f32 = 0xFFFF as f32;
+const D24_MAX: f32 = 0xFFFFFF as f32;
+
+/// 按照格式存放的深度数据，D24和D24S8都是深度在高24位
+enum DepthData
+{
+    U16(Vec<u16>),
+    U32(Vec<u32>),
+    F32(Vec<f32>),
+}
+
+/// 深度附着，D24S8格式时同时存放模板，下标是采样点的序号
+pub(super) struct DepthBuffer
+{
+    format: GLDepthFormat,
+    data: DepthData,
+}
+
+impl Default for DepthBuffer
+{
+    fn default() -> Self
+    {
+        Self { format: GLDepthFormat::D32F, data: DepthData::F32(Vec::new()) }
+    }
+}
+
+impl DepthBuffer
+{
+    /// 分配len个采样点，深度初始值为1，模板初始值为0
+    pub(super) fn new(format: GLDepthFormat, len: usize) -> Self
+    {
+        let data = match format
+        {
+            GLDepthFormat::D16 => DepthData::U16(vec![0xFFFF; len]),
+            GLDepthFormat::D24 | GLDepthFormat::D24S8 => DepthData::U32(vec![0xFFFFFF00; len]),
+            GLDepthFormat::D32F => DepthData::F32(vec![1.; len]),
+        };
+
+        Self { format, data }
+    }
+
+    pub(super) fn format(&self) -> GLDepthFormat
+    {
+        self.format
+    }
+
+    pub(super) fn is_empty(&self) -> bool
+    {
+        self.len() == 0
+    }
+
+    pub(super) fn len(&self) -> usize
+    {
+        match &self.data
+        {
+            DepthData::U16(data) => data.len(),
+            DepthData::U32(data) => data.len(),
+            DepthData::F32(data) => data.len(),
+        }
+    }
+
+    pub(super) fn as_bytes(&self) -> &[u8]
+    {
+        fn bytes<T>(data: &[T]) -> &[u8]
+        {
+            unsafe
+            {
+                &*std::ptr::slice_from_raw_parts(data.as_ptr() as *const u8, std::mem::size_of_val(data))
+            }
+        }
+
+        match &self.data
+        {
+            DepthData::U16(data) => bytes(data),
+            DepthData::U32(data) => bytes(data),
+            DepthData::F32(data) => bytes(data),
+        }
+    }
+
+    /// 得到深度写入之后再读出的值，深度测试之前用它来模拟硬件的精度
+    #[inline(always)]
+    pub(super) fn quantize(&self, depth: f32) -> f32
+    {
+        match self.format
+        {
+            GLDepthFormat::D16 => to_d16(depth) as f32 * (1. / D16_MAX),
+            GLDepthFormat::D24 | GLDepthFormat::D24S8 => to_d24(depth) as f32 * (1. / D24_MAX),
+            GLDepthFormat::D32F => depth,
+        }
+    }
+
+    #[unchecked::unchecked]
+    #[inline(always)]
+    pub(super) fn get(&self, i: usize) -> f32
+    {
+        match &self.data
+        {
+            DepthData::U16(data) => data[i] as f32 * (1. / D16_MAX),
+            DepthData::U32(data) => (data[i] >> 8) as f32 * (1. / D24_MAX),
+            DepthData::F32(data) => data[i],
+        }
+    }
+
+    #[unchecked::unchecked]
+    #[inline(always)]
+    pub(super) fn set(&mut self, i: usize, depth: f32)
+    {
+        match &mut self.data
+        {
+            DepthData::U16(data) => data[i] = to_d16(depth),
+            DepthData::U32(data) => data[i] = to_d24(depth) << 8 | (data[i] & 0xFF),
+            DepthData::F32(data) => data[i] = depth,
+        }
+    }
+
+    /// 只有D24S8格式可以调用
+    #[unchecked::unchecked]
+    #[inline(always)]
+    pub(super) fn get_stencil(&self, i: usize) -> u8
+    {
+        match &self.data
+        {
+            DepthData::U32(data) => data[i] as u8,
+            _ => 0,
+        }
+    }
+
+    #[unchecked::unchecked]
+    #[inline(always)]
+    pub(super) fn set_stencil(&mut self, i: usize, stencil: u8)
+    {
+        if let DepthData::U32(data) = &mut self.data
+        {
+            data[i] = (data[i] & 0xFFFFFF00) | stencil as u32;
+        }
+    }
+
+    /// 把[start, end)范围内的深度设为同一个值，D24S8格式的模板不变
+    pub(super) fn fill(&mut self, start: usize, end: usize, depth: f32)
+    {
+        match &mut self.data
+        {
+            DepthData::U16(data) => data[start..end].fill(to_d16(depth)),
+            DepthData::U32(data) =>
+            {
+                let depth = to_d24(depth) << 8;
+
+                for value in data[start..end].iter_mut()
+                {
+                    *value = depth | (*value & 0xFF);
+                }
+            }
+            DepthData::F32(data) => data[start..end].fill(depth),
+        }
+    }
+
+    /// 把[start, end)范围内的模板设为同一个值，只对D24S8格式有效
+    pub(super) fn fill_stencil(&mut self, start: usize, end: usize, stencil: u8)
+    {
+        if let DepthData::U32(data) = &mut self.data
+        {
+            for value in data[start..end].iter_mut()
+            {
+                *value = (*value & 0xFFFFFF00) | stencil as u32;
+            }
+        }
+    }
+}
+
+/// 限制在[0, 1]内之后四舍五入，和硬件的转换方式一致
+#[inline(always)]
+fn to_d16(depth: f32) -> u16
+{
+    (depth.clamp(0., 1.) * D16_MAX + 0.5) as u16
+}
+
+#[inline(always)]
+fn to_d24(depth: f32) -> u32
+{
+    (depth.clamp(0., 1.) as f64 * D24_MAX as f64 + 0.5) as u32
+}
\ No newline at end of file
@@ -0,0 +1,32 @@
+/// 索引缓冲的元素类型，对应GL的UNSIGNED_BYTE，UNSIGNED_SHORT和UNSIGNED_INT
+pub trait GLIndex : Clone + Copy + Send + Sync
+{
+    fn as_usize(self) -> usize;
+}
+
+impl GLIndex for u8
+{
+    #[inline(always)]
+    fn as_usize(self) -> usize
+    {
+        self as usize
+    }
+}
+
+impl GLIndex for u16
+{
+    #[inline(always)]
+    fn as_usize(self) -> usize
+    {
+        self as usize
+    }
+}
+
+impl GLIndex for u32
+{
+    #[inline(always)]
+    fn as_usize(self) -> usize
+    {
+        self as usize
+    }
+}
\ No newline at end of file
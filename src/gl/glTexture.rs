@@ -1,14 +1,31 @@
-use glam::{Vec4, Vec2};
+use std::sync::atomic::{AtomicU64, Ordering};
 
-use super::glColor::GLColor;
+use glam::{UVec4, Vec2, Vec4};
 
+use super::{glColor::GLColor, enums::{glMipmapFilter::GLMipmapFilter, glTextureFormat::GLTextureFormat}};
+
+use self::{compressed::decode_block_cached, format::{decode_texel, decode_uint, encode_texel}};
+
+mod compressed;
+mod format;
+mod mipmap;
+
+/// 纹素按照format紧密排列在data中，mipmap的格式和第0层相同。
+/// 压缩格式的data是从左上角开始按行排列的4x4块
 pub struct GLTexture
 {
+    /// 给解码缓存区分不同的纹理
+    id: u64,
     pub(crate) width: usize,
     pub(crate) height: usize,
     pub(crate) data: Vec<u8>,
+    format: GLTextureFormat,
 
     mipmaps: Vec<GLTexture>,
+
+    mipmap_filter: GLMipmapFilter,
+    mipmap_srgb: bool,
+    alpha_cutoff: Option<f32>,
 }
 
 impl GLTexture
@@ -20,93 +37,132 @@ impl GLTexture
             return None;
         }
 
-        let data = Vec::from(data);
-        
-        Some(Self {
-            width,
-            height,
-            data,
-
-            mipmaps: Vec::new(),
-        })
+        Some(Self::new(Vec::from(data), width, height, GLTextureFormat::RGBA8))
     }
 
-    /// 只支持二次幂长宽的纹理，否则不会做任何事
-    pub fn create_mipmap(&mut self, level: Option<u32>) -> bool
+    /// 直接使用data的内存，不会复制，长度必须是format.data_size(width, height)。
+    /// 压缩格式保持压缩的状态，采样时才解码用到的块
+    pub fn from_data(data: Vec<u8>, width: usize, height: usize, format: GLTextureFormat) -> Option<Self>
     {
-        if self.width & (self.width - 1) != 0 || self.height & (self.height - 1) != 0
+        if data.len() != format.data_size(width, height)
         {
-            return false;
+            return None;
         }
 
-        let level = level.unwrap_or(u32::MAX);
-        self.create_mipmap_inner(self.width, self.height, level);
-
-        return true;
+        Some(Self::new(data, width, height, format))
     }
 
-    fn create_mipmap_inner(&mut self, mut width: usize, mut height: usize, level: u32)
+    /// 把浮点数编码为format格式，比如把HDR图片保存为RGBA16F，长度必须是width * height，不支持压缩格式
+    pub fn from_vec4(values: &[Vec4], width: usize, height: usize, format: GLTextureFormat) -> Option<Self>
     {
-        if (width == 1 && height == 1) || level == 0
+        if values.len() != width * height || format.is_compressed()
         {
-            return;
+            return None;
         }
 
-        let last_mipmap = &self.mipmaps.last().unwrap_or(self).data;
-        let colors = unsafe { std::slice::from_raw_parts(last_mipmap.as_ptr() as *const GLColor, last_mipmap.len() / 4) };
+        let mut data = vec![0u8; values.len() * format.size()];
 
-        let mut result: Vec<GLColor> = Vec::new();
-
-        if width > 1 && height > 1
+        for (value, texel) in values.iter().zip(data.chunks_exact_mut(format.size()))
         {
-            let mut i = 0;
+            encode_texel(format, *value, texel);
+        }
 
-            while i < height
-            {
-                let mut j = 0;
+        Some(Self::new(data, width, height, format))
+    }
 
-                while j < width
-                {
-                    let a: Vec4 = colors[(i + 0) * width + j + 0].into();
-                    let b: Vec4 = colors[(i + 0) * width + j + 1].into();
-                    let c: Vec4 = colors[(i + 1) * width + j + 0].into();
-                    let d: Vec4 = colors[(i + 1) * width + j + 1].into();
+    fn new(data: Vec<u8>, width: usize, height: usize, format: GLTextureFormat) -> Self
+    {
+        static NEXT_ID: AtomicU64 = AtomicU64::new(0);
 
-                    result.push(((a + b + c + d) * 0.25).into());
+        Self {
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+            width,
+            height,
+            data,
+            format,
 
-                    j += 2;
-                }
+            mipmaps: Vec::new(),
 
-                i += 2;
-            }
+            mipmap_filter: GLMipmapFilter::Box,
+            mipmap_srgb: false,
+            alpha_cutoff: None,
         }
-        else
+    }
+
+    /// 直接使用colors的内存，不会复制，长度必须是width * height
+    pub fn from_colors(colors: Vec<GLColor>, width: usize, height: usize) -> Option<Self>
+    {
+        if colors.len() != width * height
         {
-            let mut i = 0;
-            let limit = usize::max(width, height);
+            return None;
+        }
 
-            while i < limit
-            {
-                let a: Vec4 = colors[i + 0].into();
-                let b: Vec4 = colors[i + 1].into();
+        //GLColor是4个u8，对齐和u8相同，所以内存可以直接交给Vec<u8>
+        let mut colors = std::mem::ManuallyDrop::new(colors);
+        let data = unsafe { Vec::from_raw_parts(colors.as_mut_ptr() as *mut u8, colors.len() * 4, colors.capacity() * 4) };
 
-                result.push(((a + b) * 0.5).into());
+        Some(Self::new(data, width, height, GLTextureFormat::RGBA8))
+    }
 
-                i += 2;
-            }   
+    /// 取出第0层的数据，mipmap会被丢弃。RGBA8格式并且容量是4的倍数时不会复制，其它格式会转换为GLColor
+    pub fn into_colors(self) -> Vec<GLColor>
+    {
+        if self.format != GLTextureFormat::RGBA8
+        {
+            return (0..self.width * self.height).map(|i| self.get_texel_at(i).into()).collect();
         }
 
-        if width > 1 { width /= 2; }
-        if height > 1 { height /= 2; }
+        if self.data.capacity() % 4 != 0
+        {
+            return self.data.chunks_exact(4).map(|c| GLColor::new(c[0], c[1], c[2], c[3])).collect();
+        }
 
-        let mut data = vec![0u8; width * height * 4];
-        data.clone_from_slice(unsafe { std::slice::from_raw_parts(result.as_ptr() as *const u8, width * height * 4) });
+        let mut data = std::mem::ManuallyDrop::new(self.data);
+        unsafe { Vec::from_raw_parts(data.as_mut_ptr() as *mut GLColor, data.len() / 4, data.capacity() / 4) }
+    }
 
-        self.mipmaps.push(Self {width, height, data, mipmaps: Vec::new()});
+    /// 取出第0层按照格式排列的原始数据，mipmap会被丢弃，压缩格式返回的是压缩的块
+    pub fn into_data(self) -> Vec<u8>
+    {
+        self.data
+    }
 
-        self.create_mipmap_inner(width, height, level - 1);
+    pub fn get_format(&self) -> GLTextureFormat
+    {
+        self.format
     }
 
+    pub fn get_width(&self) -> usize
+    {
+        self.width
+    }
+
+    pub fn get_height(&self) -> usize
+    {
+        self.height
+    }
+
+    /// 生成mipmap时使用的滤波器，默认是Box
+    pub fn set_mipmap_filter(&mut self, filter: GLMipmapFilter)
+    {
+        self.mipmap_filter = filter;
+    }
+
+    /// 为true时把颜色当作sRGB编码，先转换到线性空间再滤波，这样mipmap不会变暗。alpha始终是线性的。
+    /// SRGB8和SRGB8A8格式总是在线性空间中滤波，不需要设置
+    pub fn set_mipmap_srgb(&mut self, srgb: bool)
+    {
+        self.mipmap_srgb = srgb;
+    }
+
+    /// 给镂空纹理使用，设置之后每一层mipmap都会缩放alpha，让alpha大于cutoff的比例和第0层相同，
+    /// 这样alpha测试或者丢弃片段时远处的镂空不会越来越薄
+    pub fn set_mipmap_alpha_cutoff(&mut self, cutoff: Option<f32>)
+    {
+        self.alpha_cutoff = cutoff;
+    }
+
+    /// 压缩格式不能生成mipmap，需要用这个函数设置预先压缩好的每一层
     pub fn set_mipmap(&mut self, mipmaps: Vec<GLTexture>)
     {
         self.mipmaps = mipmaps;
@@ -114,7 +170,7 @@ impl GLTexture
 
     pub fn get_mipmap(&self, level: f32) -> &GLTexture
     {
-        if level <= 0. || self.mipmaps.len() == 0
+        if level <= 0. || self.mipmaps.is_empty()
         {
             self
         }
@@ -129,10 +185,52 @@ impl GLTexture
         Vec2::new(uv.x * (self.width as f32 - 1.), uv.y * (self.height as f32 - 1.))
     }
 
+    /// 只能用于RGBA8格式
     #[unchecked::unchecked]
     pub fn get_value(&self, st: Vec2) -> GLColor
     {
         let ptr = (&self.data[usize::min(self.data.len() - 1, (st.y as usize * self.width + st.x as usize) * 4)]) as *const _ as *const GLColor;
         unsafe { *ptr }
     }
+
+    /// 任意格式的纹素，解码为浮点数
+    pub fn get_texel(&self, st: Vec2) -> Vec4
+    {
+        self.get_texel_at(usize::min(self.width * self.height - 1, st.y as usize * self.width + st.x as usize))
+    }
+
+    /// 整数格式的原始值
+    pub fn get_uint(&self, st: Vec2) -> UVec4
+    {
+        let i = usize::min(self.width * self.height - 1, st.y as usize * self.width + st.x as usize);
+
+        if self.format.is_compressed()
+        {
+            return self.get_texel_at(i).as_uvec4();
+        }
+
+        let size = self.format.size();
+
+        decode_uint(self.format, &self.data[i * size..(i + 1) * size])
+    }
+
+    fn get_texel_at(&self, i: usize) -> Vec4
+    {
+        if self.format.is_compressed()
+        {
+            return self.get_compressed_texel(i % self.width, i / self.width);
+        }
+
+        let size = self.format.size();
+        decode_texel(self.format, &self.data[i * size..(i + 1) * size])
+    }
+
+    /// 解码(x, y)所在的块，同一个块的纹素在缓存中命中
+    fn get_compressed_texel(&self, x: usize, y: usize) -> Vec4
+    {
+        let size = self.format.size();
+        let block = y / 4 * self.width.div_ceil(4) + x / 4;
+
+        decode_block_cached(self.id, block, self.format, &self.data[block * size..(block + 1) * size])[y % 4 * 4 + x % 4]
+    }
 }
\ No newline at end of file
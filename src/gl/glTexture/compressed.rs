@@ -0,0 +1,904 @@
+use std::cell::RefCell;
+
+use glam::Vec4;
+
+use crate::gl::{enums::glTextureFormat::GLTextureFormat, util::{f16_to_f32, srgb_to_linear}};
+
+/// 一个4x4块解码之后的16个纹素，按行排列，sRGB格式已经转换到线性空间
+pub(super) type DecodedBlock = [Vec4; 16];
+
+/// BC6H以外的格式先解码为16个RGBA8纹素
+type Rgba8Block = [[u8; 4]; 16];
+
+/// 每个线程缓存的块数量，必须是2的幂
+const BLOCK_CACHE_SIZE: usize = 64;
+
+#[derive(Clone, Copy)]
+struct CachedBlock
+{
+    texture: u64,
+    block: usize,
+    texels: DecodedBlock,
+}
+
+const EMPTY_BLOCK: CachedBlock = CachedBlock { texture: u64::MAX, block: usize::MAX, texels: [Vec4::ZERO; 16] };
+
+thread_local!
+{
+    /// 直接映射的缓存，相邻的块落在相邻的位置，双线性和各向异性采样大多会命中
+    static BLOCK_CACHE: RefCell<[CachedBlock; BLOCK_CACHE_SIZE]> = const { RefCell::new([EMPTY_BLOCK; BLOCK_CACHE_SIZE]) };
+}
+
+/// 解码纹理id的第block个块，先查找当前线程的缓存
+pub(super) fn decode_block_cached(texture: u64, block: usize, format: GLTextureFormat, data: &[u8]) -> DecodedBlock
+{
+    BLOCK_CACHE.with(|cache|
+    {
+        let mut cache = cache.borrow_mut();
+        let slot = &mut cache[(block ^ (texture as usize).wrapping_mul(0x9E37_79B9)) & (BLOCK_CACHE_SIZE - 1)];
+
+        if slot.texture != texture || slot.block != block
+        {
+            *slot = CachedBlock { texture, block, texels: decode_block(format, data) };
+        }
+
+        slot.texels
+    })
+}
+
+/// 解码一个块，BC4只有红色通道，BC5只有红色和绿色通道，缺少的颜色通道是0，alpha是1
+pub(super) fn decode_block(format: GLTextureFormat, data: &[u8]) -> DecodedBlock
+{
+    //sRGB格式和对应的线性格式编码相同，解码之后再转换到线性空间
+    let linear = match format
+    {
+        GLTextureFormat::BC6HUF16 => return decode_bc6h(data, false),
+        GLTextureFormat::BC6HSF16 => return decode_bc6h(data, true),
+        GLTextureFormat::BC1SRGB => GLTextureFormat::BC1,
+        GLTextureFormat::BC2SRGB => GLTextureFormat::BC2,
+        GLTextureFormat::BC3SRGB => GLTextureFormat::BC3,
+        GLTextureFormat::BC7SRGB => GLTextureFormat::BC7,
+        GLTextureFormat::ETC2SRGB8 => GLTextureFormat::ETC2RGB8,
+        GLTextureFormat::ETC2SRGB8A8 => GLTextureFormat::ETC2RGBA8,
+        format => format,
+    };
+
+    let srgb = format.is_srgb();
+
+    decode_block_rgba8(linear, data).map(|[r, g, b, a]|
+    {
+        if srgb
+        {
+            Vec4::new(srgb_to_linear(r), srgb_to_linear(g), srgb_to_linear(b), a as f32 * (1. / 255.))
+        }
+        else
+        {
+            Vec4::new(r as f32, g as f32, b as f32, a as f32) * (1. / 255.)
+        }
+    })
+}
+
+fn decode_block_rgba8(format: GLTextureFormat, data: &[u8]) -> Rgba8Block
+{
+    let mut out = [[0, 0, 0, 255]; 16];
+
+    match format
+    {
+        GLTextureFormat::BC1 => decode_bc1(data, &mut out, false),
+        GLTextureFormat::BC2 =>
+        {
+            decode_bc1(&data[8..16], &mut out, true);
+
+            let alpha = u64::from_le_bytes(data[0..8].try_into().unwrap());
+            for (i, texel) in out.iter_mut().enumerate()
+            {
+                texel[3] = ((alpha >> (i * 4)) & 0xF) as u8 * 17;
+            }
+        }
+        GLTextureFormat::BC3 =>
+        {
+            decode_bc1(&data[8..16], &mut out, true);
+
+            for (texel, alpha) in out.iter_mut().zip(decode_bc4(&data[0..8]))
+            {
+                texel[3] = alpha;
+            }
+        }
+        GLTextureFormat::BC4 =>
+        {
+            for (texel, red) in out.iter_mut().zip(decode_bc4(data))
+            {
+                texel[0] = red;
+            }
+        }
+        GLTextureFormat::BC5 =>
+        {
+            for ((texel, red), green) in out.iter_mut().zip(decode_bc4(&data[0..8])).zip(decode_bc4(&data[8..16]))
+            {
+                texel[0] = red;
+                texel[1] = green;
+            }
+        }
+        GLTextureFormat::BC7 => decode_bc7(data, &mut out),
+        GLTextureFormat::ETC2RGB8 => decode_etc2_rgb(data, &mut out),
+        GLTextureFormat::ETC2RGBA8 =>
+        {
+            decode_etc2_rgb(&data[8..16], &mut out);
+            decode_eac_alpha(&data[0..8], &mut out);
+        }
+        _ => unreachable!(),
+    }
+
+    out
+}
+
+fn rgb565(color: u16) -> [u32; 3]
+{
+    let r = (color >> 11) as u32 & 0x1F;
+    let g = (color >> 5) as u32 & 0x3F;
+    let b = color as u32 & 0x1F;
+
+    [(r << 3) | (r >> 2), (g << 2) | (g >> 4), (b << 3) | (b >> 2)]
+}
+
+/// BC1的颜色部分，BC2和BC3中总是使用4种颜色的模式
+fn decode_bc1(data: &[u8], out: &mut Rgba8Block, four_colors: bool)
+{
+    let c0 = u16::from_le_bytes([data[0], data[1]]);
+    let c1 = u16::from_le_bytes([data[2], data[3]]);
+    let (p0, p1) = (rgb565(c0), rgb565(c1));
+
+    let mut palette = [[0u8; 4]; 4];
+
+    for c in 0..3
+    {
+        palette[0][c] = p0[c] as u8;
+        palette[1][c] = p1[c] as u8;
+
+        if c0 > c1 || four_colors
+        {
+            palette[2][c] = ((2 * p0[c] + p1[c] + 1) / 3) as u8;
+            palette[3][c] = ((p0[c] + 2 * p1[c] + 1) / 3) as u8;
+        }
+        else
+        {
+            palette[2][c] = (p0[c] + p1[c]).div_ceil(2) as u8;
+        }
+    }
+
+    palette[0][3] = 255;
+    palette[1][3] = 255;
+    palette[2][3] = 255;
+    //3种颜色的模式中第4种是透明的黑色
+    palette[3][3] = if c0 > c1 || four_colors { 255 } else { 0 };
+
+    let indices = u32::from_le_bytes([data[4], data[5], data[6], data[7]]);
+
+    for (i, texel) in out.iter_mut().enumerate()
+    {
+        *texel = palette[((indices >> (i * 2)) & 3) as usize];
+    }
+}
+
+/// 单通道的块，BC3的alpha和BC5的两个通道也使用这种编码
+fn decode_bc4(data: &[u8]) -> [u8; 16]
+{
+    let (a0, a1) = (data[0] as u32, data[1] as u32);
+    let mut palette = [a0, a1, 0, 0, 0, 0, 0, 255];
+
+    if a0 > a1
+    {
+        for i in 1..7
+        {
+            palette[i as usize + 1] = ((7 - i) * a0 + i * a1) / 7;
+        }
+    }
+    else
+    {
+        for i in 1..5
+        {
+            palette[i as usize + 1] = ((5 - i) * a0 + i * a1) / 5;
+        }
+    }
+
+    let mut bits = [0u8; 8];
+    bits[..6].copy_from_slice(&data[2..8]);
+    let indices = u64::from_le_bytes(bits);
+
+    std::array::from_fn(|i| palette[((indices >> (i * 3)) & 7) as usize] as u8)
+}
+
+/// BC7每种模式的参数
+struct BC7Mode
+{
+    subsets: usize,
+    partition_bits: u32,
+    rotation_bits: u32,
+    index_selection_bits: u32,
+    color_bits: u32,
+    alpha_bits: u32,
+    /// 每个端点一个p位
+    endpoint_pbits: bool,
+    /// 每个子集一个共用的p位
+    shared_pbits: bool,
+    index_bits: u32,
+    index_bits2: u32,
+}
+
+const BC7_MODES: [BC7Mode; 8] = [
+    BC7Mode { subsets: 3, partition_bits: 4, rotation_bits: 0, index_selection_bits: 0, color_bits: 4, alpha_bits: 0, endpoint_pbits: true, shared_pbits: false, index_bits: 3, index_bits2: 0 },
+    BC7Mode { subsets: 2, partition_bits: 6, rotation_bits: 0, index_selection_bits: 0, color_bits: 6, alpha_bits: 0, endpoint_pbits: false, shared_pbits: true, index_bits: 3, index_bits2: 0 },
+    BC7Mode { subsets: 3, partition_bits: 6, rotation_bits: 0, index_selection_bits: 0, color_bits: 5, alpha_bits: 0, endpoint_pbits: false, shared_pbits: false, index_bits: 2, index_bits2: 0 },
+    BC7Mode { subsets: 2, partition_bits: 6, rotation_bits: 0, index_selection_bits: 0, color_bits: 7, alpha_bits: 0, endpoint_pbits: true, shared_pbits: false, index_bits: 2, index_bits2: 0 },
+    BC7Mode { subsets: 1, partition_bits: 0, rotation_bits: 2, index_selection_bits: 1, color_bits: 5, alpha_bits: 6, endpoint_pbits: false, shared_pbits: false, index_bits: 2, index_bits2: 3 },
+    BC7Mode { subsets: 1, partition_bits: 0, rotation_bits: 2, index_selection_bits: 0, color_bits: 7, alpha_bits: 8, endpoint_pbits: false, shared_pbits: false, index_bits: 2, index_bits2: 2 },
+    BC7Mode { subsets: 1, partition_bits: 0, rotation_bits: 0, index_selection_bits: 0, color_bits: 7, alpha_bits: 7, endpoint_pbits: true, shared_pbits: false, index_bits: 4, index_bits2: 0 },
+    BC7Mode { subsets: 2, partition_bits: 6, rotation_bits: 0, index_selection_bits: 0, color_bits: 5, alpha_bits: 5, endpoint_pbits: true, shared_pbits: false, index_bits: 2, index_bits2: 0 },
+];
+
+/// 2个子集的划分，第i位是第i个纹素所在的子集
+const BC7_PARTITIONS2: [u16; 64] = [
+    0xCCCC, 0x8888, 0xEEEE, 0xECC8, 0xC880, 0xFEEC, 0xFEC8, 0xEC80, 0xC800, 0xFFEC, 0xFE80, 0xE800, 0xFFE8, 0xFF00, 0xFFF0, 0xF000,
+    0xF710, 0x008E, 0x7100, 0x08CE, 0x008C, 0x7310, 0x3100, 0x8CCE, 0x088C, 0x3110, 0x6666, 0x366C, 0x17E8, 0x0FF0, 0x718E, 0x399C,
+    0xAAAA, 0xF0F0, 0x5A5A, 0x33CC, 0x3C3C, 0x55AA, 0x9696, 0xA55A, 0x73CE, 0x13C8, 0x324C, 0x3BDC, 0x6996, 0xC33C, 0x9966, 0x0660,
+    0x0272, 0x04E4, 0x4E40, 0x2720, 0xC936, 0x936C, 0x39C6, 0x639C, 0x9336, 0x9CC6, 0x817E, 0xE718, 0xCCF0, 0x0FCC, 0x7744, 0xEE22,
+];
+
+/// 3个子集的划分
+const BC7_PARTITIONS3: [[u8; 16]; 64] = [
+    [0, 0, 1, 1, 0, 0, 1, 1, 0, 2, 2, 1, 2, 2, 2, 2], [0, 0, 0, 1, 0, 0, 1, 1, 2, 2, 1, 1, 2, 2, 2, 1],
+    [0, 0, 0, 0, 2, 0, 0, 1, 2, 2, 1, 1, 2, 2, 1, 1], [0, 2, 2, 2, 0, 0, 2, 2, 0, 0, 1, 1, 0, 1, 1, 1],
+    [0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 2, 2, 1, 1, 2, 2], [0, 0, 1, 1, 0, 0, 1, 1, 0, 0, 2, 2, 0, 0, 2, 2],
+    [0, 0, 2, 2, 0, 0, 2, 2, 1, 1, 1, 1, 1, 1, 1, 1], [0, 0, 1, 1, 0, 0, 1, 1, 2, 2, 1, 1, 2, 2, 1, 1],
+    [0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2], [0, 0, 0, 0, 1, 1, 1, 1, 1, 1, 1, 1, 2, 2, 2, 2],
+    [0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 2, 2, 2, 2], [0, 0, 1, 2, 0, 0, 1, 2, 0, 0, 1, 2, 0, 0, 1, 2],
+    [0, 1, 1, 2, 0, 1, 1, 2, 0, 1, 1, 2, 0, 1, 1, 2], [0, 1, 2, 2, 0, 1, 2, 2, 0, 1, 2, 2, 0, 1, 2, 2],
+    [0, 0, 1, 1, 0, 1, 1, 2, 1, 1, 2, 2, 1, 2, 2, 2], [0, 0, 1, 1, 2, 0, 0, 1, 2, 2, 0, 0, 2, 2, 2, 0],
+    [0, 0, 0, 1, 0, 0, 1, 1, 0, 1, 1, 2, 1, 1, 2, 2], [0, 1, 1, 1, 0, 0, 1, 1, 2, 0, 0, 1, 2, 2, 0, 0],
+    [0, 0, 0, 0, 1, 1, 2, 2, 1, 1, 2, 2, 1, 1, 2, 2], [0, 0, 2, 2, 0, 0, 2, 2, 0, 0, 2, 2, 1, 1, 1, 1],
+    [0, 1, 1, 1, 0, 1, 1, 1, 0, 2, 2, 2, 0, 2, 2, 2], [0, 0, 0, 1, 0, 0, 0, 1, 2, 2, 2, 1, 2, 2, 2, 1],
+    [0, 0, 0, 0, 0, 0, 1, 1, 0, 1, 2, 2, 0, 1, 2, 2], [0, 0, 0, 0, 1, 1, 0, 0, 2, 2, 1, 0, 2, 2, 1, 0],
+    [0, 1, 2, 2, 0, 1, 2, 2, 0, 0, 1, 1, 0, 0, 0, 0], [0, 0, 1, 2, 0, 0, 1, 2, 1, 1, 2, 2, 2, 2, 2, 2],
+    [0, 1, 1, 0, 1, 2, 2, 1, 1, 2, 2, 1, 0, 1, 1, 0], [0, 0, 0, 0, 0, 1, 1, 0, 1, 2, 2, 1, 1, 2, 2, 1],
+    [0, 0, 2, 2, 1, 1, 0, 2, 1, 1, 0, 2, 0, 0, 2, 2], [0, 1, 1, 0, 0, 1, 1, 0, 2, 0, 0, 2, 2, 2, 2, 2],
+    [0, 0, 1, 1, 0, 1, 2, 2, 0, 1, 2, 2, 0, 0, 1, 1], [0, 0, 0, 0, 2, 0, 0, 0, 2, 2, 1, 1, 2, 2, 2, 1],
+    [0, 0, 0, 0, 0, 0, 0, 2, 1, 1, 2, 2, 1, 2, 2, 2], [0, 2, 2, 2, 0, 0, 2, 2, 0, 0, 1, 2, 0, 0, 1, 1],
+    [0, 0, 1, 1, 0, 0, 1, 2, 0, 0, 2, 2, 0, 2, 2, 2], [0, 1, 2, 0, 0, 1, 2, 0, 0, 1, 2, 0, 0, 1, 2, 0],
+    [0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 0, 0, 0, 0], [0, 1, 2, 0, 1, 2, 0, 1, 2, 0, 1, 2, 0, 1, 2, 0],
+    [0, 1, 2, 0, 2, 0, 1, 2, 1, 2, 0, 1, 0, 1, 2, 0], [0, 0, 1, 1, 2, 2, 0, 0, 1, 1, 2, 2, 0, 0, 1, 1],
+    [0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 0, 0, 0, 0, 1, 1], [0, 1, 0, 1, 0, 1, 0, 1, 2, 2, 2, 2, 2, 2, 2, 2],
+    [0, 0, 0, 0, 0, 0, 0, 0, 2, 1, 2, 1, 2, 1, 2, 1], [0, 0, 2, 2, 1, 1, 2, 2, 0, 0, 2, 2, 1, 1, 2, 2],
+    [0, 0, 2, 2, 0, 0, 1, 1, 0, 0, 2, 2, 0, 0, 1, 1], [0, 2, 2, 0, 1, 2, 2, 1, 0, 2, 2, 0, 1, 2, 2, 1],
+    [0, 1, 0, 1, 2, 2, 2, 2, 2, 2, 2, 2, 0, 1, 0, 1], [0, 0, 0, 0, 2, 1, 2, 1, 2, 1, 2, 1, 2, 1, 2, 1],
+    [0, 1, 0, 1, 0, 1, 0, 1, 0, 1, 0, 1, 2, 2, 2, 2], [0, 2, 2, 2, 0, 1, 1, 1, 0, 2, 2, 2, 0, 1, 1, 1],
+    [0, 0, 0, 2, 1, 1, 1, 2, 0, 0, 0, 2, 1, 1, 1, 2], [0, 0, 0, 0, 2, 1, 1, 2, 2, 1, 1, 2, 2, 1, 1, 2],
+    [0, 2, 2, 2, 0, 1, 1, 1, 0, 1, 1, 1, 0, 2, 2, 2], [0, 0, 0, 2, 1, 1, 1, 2, 1, 1, 1, 2, 0, 0, 0, 2],
+    [0, 1, 1, 0, 0, 1, 1, 0, 0, 1, 1, 0, 2, 2, 2, 2], [0, 0, 0, 0, 0, 0, 0, 0, 2, 1, 1, 2, 2, 1, 1, 2],
+    [0, 1, 1, 0, 0, 1, 1, 0, 2, 2, 2, 2, 2, 2, 2, 2], [0, 0, 2, 2, 0, 0, 1, 1, 0, 0, 1, 1, 0, 0, 2, 2],
+    [0, 0, 2, 2, 1, 1, 2, 2, 1, 1, 2, 2, 0, 0, 2, 2], [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2, 1, 1, 2],
+    [0, 0, 0, 2, 0, 0, 0, 1, 0, 0, 0, 2, 0, 0, 0, 1], [0, 2, 2, 2, 1, 2, 2, 2, 0, 2, 2, 2, 1, 2, 2, 2],
+    [0, 1, 0, 1, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2], [0, 1, 1, 1, 2, 0, 1, 1, 2, 2, 0, 1, 2, 2, 2, 0],
+];
+
+/// 2个子集时第1个子集的锚点纹素
+const BC7_ANCHORS2: [u8; 64] = [
+    15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15,
+    15, 2, 8, 2, 2, 8, 8, 15, 2, 8, 2, 2, 8, 8, 2, 2,
+    15, 15, 6, 8, 2, 8, 15, 15, 2, 8, 2, 2, 2, 15, 15, 6,
+    6, 2, 6, 8, 15, 15, 2, 2, 15, 15, 15, 15, 15, 2, 2, 15,
+];
+
+/// 3个子集时第1个和第2个子集的锚点纹素
+const BC7_ANCHORS3: [[u8; 64]; 2] = [
+    [
+        3, 3, 15, 15, 8, 3, 15, 15, 8, 8, 6, 6, 6, 5, 3, 3,
+        3, 3, 8, 15, 3, 3, 6, 10, 5, 8, 8, 6, 8, 5, 15, 15,
+        8, 15, 3, 5, 6, 10, 8, 15, 15, 3, 15, 5, 15, 15, 15, 15,
+        3, 15, 5, 5, 5, 8, 5, 10, 5, 10, 8, 13, 15, 12, 3, 3,
+    ],
+    [
+        15, 8, 8, 3, 15, 15, 3, 8, 15, 15, 15, 15, 15, 15, 15, 8,
+        15, 8, 15, 3, 15, 8, 15, 8, 3, 15, 6, 10, 15, 15, 10, 8,
+        15, 3, 15, 10, 10, 8, 9, 10, 6, 15, 8, 15, 3, 6, 6, 8,
+        15, 3, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 3, 15, 15, 8,
+    ],
+];
+
+const BC7_WEIGHTS2: [u32; 4] = [0, 21, 43, 64];
+const BC7_WEIGHTS3: [u32; 8] = [0, 9, 18, 27, 37, 46, 55, 64];
+const BC7_WEIGHTS4: [u32; 16] = [0, 4, 9, 13, 17, 21, 26, 30, 34, 38, 43, 47, 51, 55, 60, 64];
+
+/// 从低位开始读取的位流
+struct BitReader
+{
+    bits: u128,
+    pos: u32,
+}
+
+impl BitReader
+{
+    fn read(&mut self, count: u32) -> u32
+    {
+        let value = ((self.bits >> self.pos) & ((1u128 << count) - 1)) as u32;
+        self.pos += count;
+
+        value
+    }
+}
+
+fn bc7_interpolate(e0: u32, e1: u32, index: u32, bits: u32) -> u8
+{
+    let weight = match bits
+    {
+        2 => BC7_WEIGHTS2[index as usize],
+        3 => BC7_WEIGHTS3[index as usize],
+        _ => BC7_WEIGHTS4[index as usize],
+    };
+
+    (((64 - weight) * e0 + weight * e1 + 32) >> 6) as u8
+}
+
+fn decode_bc7(data: &[u8], out: &mut Rgba8Block)
+{
+    let mut reader = BitReader { bits: u128::from_le_bytes(data[0..16].try_into().unwrap()), pos: 0 };
+
+    //模式是第一个1之前0的数量，没有1的块是保留的，解码为透明的黑色
+    let Some(mode) = (0..8).find(|_| reader.read(1) == 1)
+    else
+    {
+        *out = [[0; 4]; 16];
+        return;
+    };
+
+    let m = &BC7_MODES[mode];
+    let partition = reader.read(m.partition_bits) as usize;
+    let rotation = reader.read(m.rotation_bits);
+    let index_selection = reader.read(m.index_selection_bits);
+
+    let endpoint_count = m.subsets * 2;
+    let mut endpoints = [[0u32; 4]; 6];
+
+    for c in 0..3
+    {
+        for endpoint in &mut endpoints[..endpoint_count]
+        {
+            endpoint[c] = reader.read(m.color_bits);
+        }
+    }
+
+    for endpoint in &mut endpoints[..endpoint_count]
+    {
+        endpoint[3] = reader.read(m.alpha_bits);
+    }
+
+    let (mut color_bits, mut alpha_bits) = (m.color_bits, m.alpha_bits);
+
+    if m.endpoint_pbits || m.shared_pbits
+    {
+        let mut pbits = [0u32; 6];
+
+        if m.endpoint_pbits
+        {
+            for pbit in &mut pbits[..endpoint_count]
+            {
+                *pbit = reader.read(1);
+            }
+        }
+        else
+        {
+            for subset in 0..m.subsets
+            {
+                let pbit = reader.read(1);
+                pbits[subset * 2] = pbit;
+                pbits[subset * 2 + 1] = pbit;
+            }
+        }
+
+        for (endpoint, pbit) in endpoints[..endpoint_count].iter_mut().zip(pbits)
+        {
+            for value in endpoint.iter_mut()
+            {
+                *value = (*value << 1) | pbit;
+            }
+        }
+
+        color_bits += 1;
+        if alpha_bits > 0 { alpha_bits += 1; }
+    }
+
+    for endpoint in &mut endpoints[..endpoint_count]
+    {
+        for (c, value) in endpoint.iter_mut().enumerate()
+        {
+            let bits = if c < 3 { color_bits } else { alpha_bits };
+
+            *value = if bits == 0 { 255 } else { (*value << (8 - bits)) | (*value << (8 - bits) >> bits) };
+        }
+    }
+
+    let subset_of = |i: usize| match m.subsets
+    {
+        1 => 0,
+        2 => ((BC7_PARTITIONS2[partition] >> i) & 1) as usize,
+        _ => BC7_PARTITIONS3[partition][i] as usize,
+    };
+
+    let is_anchor = |i: usize| match (m.subsets, subset_of(i))
+    {
+        (_, 0) => i == 0,
+        (2, _) => i == BC7_ANCHORS2[partition] as usize,
+        (_, subset) => i == BC7_ANCHORS3[subset - 1][partition] as usize,
+    };
+
+    //锚点纹素的索引省略了最高位
+    let mut indices = [0u32; 16];
+    for (i, index) in indices.iter_mut().enumerate()
+    {
+        *index = reader.read(m.index_bits - is_anchor(i) as u32);
+    }
+
+    let mut indices2 = [0u32; 16];
+    if m.index_bits2 > 0
+    {
+        for (i, index) in indices2.iter_mut().enumerate()
+        {
+            *index = reader.read(m.index_bits2 - (i == 0) as u32);
+        }
+    }
+
+    for (i, texel) in out.iter_mut().enumerate()
+    {
+        let subset = subset_of(i);
+        let (e0, e1) = (&endpoints[subset * 2], &endpoints[subset * 2 + 1]);
+
+        //模式4和5的颜色和alpha使用不同的索引，index_selection为1时交换
+        let (color_index, color_index_bits, alpha_index, alpha_index_bits) = if m.index_bits2 == 0
+        {
+            (indices[i], m.index_bits, indices[i], m.index_bits)
+        }
+        else if index_selection == 0
+        {
+            (indices[i], m.index_bits, indices2[i], m.index_bits2)
+        }
+        else
+        {
+            (indices2[i], m.index_bits2, indices[i], m.index_bits)
+        };
+
+        for c in 0..3
+        {
+            texel[c] = bc7_interpolate(e0[c], e1[c], color_index, color_index_bits);
+        }
+        texel[3] = bc7_interpolate(e0[3], e1[3], alpha_index, alpha_index_bits);
+
+        match rotation
+        {
+            1 => texel.swap(0, 3),
+            2 => texel.swap(1, 3),
+            3 => texel.swap(2, 3),
+            _ => {}
+        }
+    }
+}
+
+/// BC6H端点在位流中的字段，按端点*3+通道编号
+const R0: u8 = 0;
+const G0: u8 = 1;
+const B0: u8 = 2;
+const R1: u8 = 3;
+const G1: u8 = 4;
+const B1: u8 = 5;
+const R2: u8 = 6;
+const G2: u8 = 7;
+const B2: u8 = 8;
+const R3: u8 = 9;
+const G3: u8 = 10;
+const B3: u8 = 11;
+
+/// BC6H每种模式的参数
+struct BC6HMode
+{
+    /// 块开头的模式位，前两位小于2时只有2位，否则有5位
+    id: u32,
+    two_regions: bool,
+    /// 除了第一个端点，其它端点保存的是和第一个端点的差值
+    transformed: bool,
+    endpoint_bits: u32,
+    delta_bits: [u32; 3],
+    /// 端点的每一段位(字段, a, b)，a >= b时按b..=a的顺序保存，否则倒序保存
+    layout: &'static [(u8, u8, u8)],
+}
+
+const BC6H_MODES: [BC6HMode; 14] = [
+    BC6HMode { id: 0, two_regions: true, transformed: true, endpoint_bits: 10, delta_bits: [5, 5, 5], layout: &[
+        (G2, 4, 4), (B2, 4, 4), (B3, 4, 4), (R0, 9, 0), (G0, 9, 0), (B0, 9, 0), (R1, 4, 0), (G3, 4, 4), (G2, 3, 0), (G1, 4, 0),
+        (B3, 0, 0), (G3, 3, 0), (B1, 4, 0), (B3, 1, 1), (B2, 3, 0), (R2, 4, 0), (B3, 2, 2), (R3, 4, 0), (B3, 3, 3)] },
+    BC6HMode { id: 1, two_regions: true, transformed: true, endpoint_bits: 7, delta_bits: [6, 6, 6], layout: &[
+        (G2, 5, 5), (G3, 4, 4), (G3, 5, 5), (R0, 6, 0), (B3, 0, 0), (B3, 1, 1), (B2, 4, 4), (G0, 6, 0), (B2, 5, 5), (B3, 2, 2),
+        (G2, 4, 4), (B0, 6, 0), (B3, 3, 3), (B3, 5, 5), (B3, 4, 4), (R1, 5, 0), (G2, 3, 0), (G1, 5, 0), (G3, 3, 0), (B1, 5, 0),
+        (B2, 3, 0), (R2, 5, 0), (R3, 5, 0)] },
+    BC6HMode { id: 2, two_regions: true, transformed: true, endpoint_bits: 11, delta_bits: [5, 4, 4], layout: &[
+        (R0, 9, 0), (G0, 9, 0), (B0, 9, 0), (R1, 4, 0), (R0, 10, 10), (G2, 3, 0), (G1, 3, 0), (G0, 10, 10), (B3, 0, 0), (G3, 3, 0),
+        (B1, 3, 0), (B0, 10, 10), (B3, 1, 1), (B2, 3, 0), (R2, 4, 0), (B3, 2, 2), (R3, 4, 0), (B3, 3, 3)] },
+    BC6HMode { id: 6, two_regions: true, transformed: true, endpoint_bits: 11, delta_bits: [4, 5, 4], layout: &[
+        (R0, 9, 0), (G0, 9, 0), (B0, 9, 0), (R1, 3, 0), (R0, 10, 10), (G3, 4, 4), (G2, 3, 0), (G1, 4, 0), (G0, 10, 10), (G3, 3, 0),
+        (B1, 3, 0), (B0, 10, 10), (B3, 1, 1), (B2, 3, 0), (R2, 3, 0), (B3, 0, 0), (B3, 2, 2), (R3, 3, 0), (G2, 4, 4), (B3, 3, 3)] },
+    BC6HMode { id: 10, two_regions: true, transformed: true, endpoint_bits: 11, delta_bits: [4, 4, 5], layout: &[
+        (R0, 9, 0), (G0, 9, 0), (B0, 9, 0), (R1, 3, 0), (R0, 10, 10), (B2, 4, 4), (G2, 3, 0), (G1, 3, 0), (G0, 10, 10), (B3, 0, 0),
+        (G3, 3, 0), (B1, 4, 0), (B0, 10, 10), (B2, 3, 0), (R2, 3, 0), (B3, 1, 1), (B3, 2, 2), (R3, 3, 0), (B3, 4, 4), (B3, 3, 3)] },
+    BC6HMode { id: 14, two_regions: true, transformed: true, endpoint_bits: 9, delta_bits: [5, 5, 5], layout: &[
+        (R0, 8, 0), (B2, 4, 4), (G0, 8, 0), (G2, 4, 4), (B0, 8, 0), (B3, 4, 4), (R1, 4, 0), (G3, 4, 4), (G2, 3, 0), (G1, 4, 0),
+        (B3, 0, 0), (G3, 3, 0), (B1, 4, 0), (B3, 1, 1), (B2, 3, 0), (R2, 4, 0), (B3, 2, 2), (R3, 4, 0), (B3, 3, 3)] },
+    BC6HMode { id: 18, two_regions: true, transformed: true, endpoint_bits: 8, delta_bits: [6, 5, 5], layout: &[
+        (R0, 7, 0), (G3, 4, 4), (B2, 4, 4), (G0, 7, 0), (B3, 2, 2), (G2, 4, 4), (B0, 7, 0), (B3, 3, 3), (B3, 4, 4), (R1, 5, 0),
+        (G2, 3, 0), (G1, 4, 0), (B3, 0, 0), (G3, 3, 0), (B1, 4, 0), (B3, 1, 1), (B2, 3, 0), (R2, 5, 0), (R3, 5, 0)] },
+    BC6HMode { id: 22, two_regions: true, transformed: true, endpoint_bits: 8, delta_bits: [5, 6, 5], layout: &[
+        (R0, 7, 0), (B3, 0, 0), (B2, 4, 4), (G0, 7, 0), (G2, 5, 5), (G2, 4, 4), (B0, 7, 0), (G3, 5, 5), (B3, 4, 4), (R1, 4, 0),
+        (G3, 4, 4), (G2, 3, 0), (G1, 5, 0), (G3, 3, 0), (B1, 4, 0), (B3, 1, 1), (B2, 3, 0), (R2, 4, 0), (B3, 2, 2), (R3, 4, 0),
+        (B3, 3, 3)] },
+    BC6HMode { id: 26, two_regions: true, transformed: true, endpoint_bits: 8, delta_bits: [5, 5, 6], layout: &[
+        (R0, 7, 0), (B3, 1, 1), (B2, 4, 4), (G0, 7, 0), (B2, 5, 5), (G2, 4, 4), (B0, 7, 0), (B3, 5, 5), (B3, 4, 4), (R1, 4, 0),
+        (G3, 4, 4), (G2, 3, 0), (G1, 4, 0), (B3, 0, 0), (G3, 3, 0), (B1, 5, 0), (B2, 3, 0), (R2, 4, 0), (B3, 2, 2), (R3, 4, 0),
+        (B3, 3, 3)] },
+    BC6HMode { id: 30, two_regions: true, transformed: false, endpoint_bits: 6, delta_bits: [6, 6, 6], layout: &[
+        (R0, 5, 0), (G3, 4, 4), (B3, 0, 0), (B3, 1, 1), (B2, 4, 4), (G0, 5, 0), (G2, 5, 5), (B2, 5, 5), (B3, 2, 2), (G2, 4, 4),
+        (B0, 5, 0), (G3, 5, 5), (B3, 3, 3), (B3, 5, 5), (B3, 4, 4), (R1, 5, 0), (G2, 3, 0), (G1, 5, 0), (G3, 3, 0), (B1, 5, 0),
+        (B2, 3, 0), (R2, 5, 0), (R3, 5, 0)] },
+    BC6HMode { id: 3, two_regions: false, transformed: false, endpoint_bits: 10, delta_bits: [10, 10, 10], layout: &[
+        (R0, 9, 0), (G0, 9, 0), (B0, 9, 0), (R1, 9, 0), (G1, 9, 0), (B1, 9, 0)] },
+    BC6HMode { id: 7, two_regions: false, transformed: true, endpoint_bits: 11, delta_bits: [9, 9, 9], layout: &[
+        (R0, 9, 0), (G0, 9, 0), (B0, 9, 0), (R1, 8, 0), (R0, 10, 10), (G1, 8, 0), (G0, 10, 10), (B1, 8, 0), (B0, 10, 10)] },
+    BC6HMode { id: 11, two_regions: false, transformed: true, endpoint_bits: 12, delta_bits: [8, 8, 8], layout: &[
+        (R0, 9, 0), (G0, 9, 0), (B0, 9, 0), (R1, 7, 0), (R0, 10, 11), (G1, 7, 0), (G0, 10, 11), (B1, 7, 0), (B0, 10, 11)] },
+    BC6HMode { id: 15, two_regions: false, transformed: true, endpoint_bits: 16, delta_bits: [4, 4, 4], layout: &[
+        (R0, 9, 0), (G0, 9, 0), (B0, 9, 0), (R1, 3, 0), (R0, 10, 15), (G1, 3, 0), (G0, 10, 15), (B1, 3, 0), (B0, 10, 15)] },
+];
+
+fn sign_extend(value: i32, bits: u32) -> i32
+{
+    (value << (32 - bits)) >> (32 - bits)
+}
+
+/// 把端点从endpoint_bits位扩展到16位
+fn bc6h_unquantize(value: i32, bits: u32, signed: bool) -> i32
+{
+    if !signed
+    {
+        if bits >= 15 || value == 0
+        {
+            value
+        }
+        else if value == (1 << bits) - 1
+        {
+            0xFFFF
+        }
+        else
+        {
+            ((value << 16) + 0x8000) >> bits
+        }
+    }
+    else if bits >= 16
+    {
+        value
+    }
+    else
+    {
+        let abs = if value == 0
+        {
+            0
+        }
+        else if value.abs() >= (1 << (bits - 1)) - 1
+        {
+            0x7FFF
+        }
+        else
+        {
+            ((value.abs() << 15) + 0x4000) >> (bits - 1)
+        };
+
+        if value < 0 { -abs } else { abs }
+    }
+}
+
+/// 插值之后缩放到半精度浮点数的范围内，再按照半精度浮点数的位解释
+fn bc6h_to_f32(value: i32, signed: bool) -> f32
+{
+    let half = if !signed
+    {
+        (value * 31) >> 6
+    }
+    else if value < 0
+    {
+        0x8000 | ((-value * 31) >> 5)
+    }
+    else
+    {
+        (value * 31) >> 5
+    };
+
+    f16_to_f32(half as u16)
+}
+
+/// BC6H解码为半精度浮点数的RGB，alpha是1。划分和插值权重和BC7相同，2个区域时只使用前32种划分
+fn decode_bc6h(data: &[u8], signed: bool) -> DecodedBlock
+{
+    let mut reader = BitReader { bits: u128::from_le_bytes(data[0..16].try_into().unwrap()), pos: 0 };
+
+    let mut id = reader.read(2);
+    if id > 1
+    {
+        id |= reader.read(3) << 2;
+    }
+
+    //保留的模式解码为黑色
+    let Some(m) = BC6H_MODES.iter().find(|m| m.id == id)
+    else
+    {
+        return [Vec4::W; 16];
+    };
+
+    let mut endpoints = [[0i32; 3]; 4];
+
+    for &(field, a, b) in m.layout
+    {
+        let value = &mut endpoints[field as usize / 3][field as usize % 3];
+
+        if a >= b
+        {
+            *value |= (reader.read((a - b + 1) as u32) << b) as i32;
+        }
+        else
+        {
+            for bit in (a..=b).rev()
+            {
+                *value |= (reader.read(1) << bit) as i32;
+            }
+        }
+    }
+
+    let partition = if m.two_regions { reader.read(5) as usize } else { 0 };
+    let endpoint_count = if m.two_regions { 4 } else { 2 };
+
+    if signed
+    {
+        for value in endpoints[0].iter_mut()
+        {
+            *value = sign_extend(*value, m.endpoint_bits);
+        }
+    }
+
+    //差值总是有符号的，没有变换的端点只在有符号格式中需要扩展符号
+    if m.transformed || signed
+    {
+        for endpoint in &mut endpoints[1..endpoint_count]
+        {
+            for (value, bits) in endpoint.iter_mut().zip(m.delta_bits)
+            {
+                *value = sign_extend(*value, bits);
+            }
+        }
+    }
+
+    if m.transformed
+    {
+        let base = endpoints[0];
+
+        for endpoint in &mut endpoints[1..endpoint_count]
+        {
+            for (value, base) in endpoint.iter_mut().zip(base)
+            {
+                *value = (*value + base) & ((1 << m.endpoint_bits) - 1);
+
+                if signed
+                {
+                    *value = sign_extend(*value, m.endpoint_bits);
+                }
+            }
+        }
+    }
+
+    for endpoint in &mut endpoints[..endpoint_count]
+    {
+        for value in endpoint.iter_mut()
+        {
+            *value = bc6h_unquantize(*value, m.endpoint_bits, signed);
+        }
+    }
+
+    let (index_bits, weights) = if m.two_regions { (3, &BC7_WEIGHTS3[..]) } else { (4, &BC7_WEIGHTS4[..]) };
+
+    std::array::from_fn(|i|
+    {
+        let subset = if m.two_regions { ((BC7_PARTITIONS2[partition] >> i) & 1) as usize } else { 0 };
+        let is_anchor = i == 0 || (subset == 1 && i == BC7_ANCHORS2[partition] as usize);
+
+        let weight = weights[reader.read(index_bits - is_anchor as u32) as usize] as i32;
+        let (e0, e1) = (endpoints[subset * 2], endpoints[subset * 2 + 1]);
+        let color = |c: usize| bc6h_to_f32(((64 - weight) * e0[c] + weight * e1[c] + 32) >> 6, signed);
+
+        Vec4::new(color(0), color(1), color(2), 1.)
+    })
+}
+
+/// ETC1的亮度修正表，每一行是较小和较大的修正值
+const ETC1_MODIFIERS: [[i32; 2]; 8] = [[2, 8], [5, 17], [9, 29], [13, 42], [18, 60], [24, 80], [33, 106], [47, 183]];
+
+/// ETC2的T和H模式的距离表
+const ETC2_DISTANCES: [i32; 8] = [3, 6, 11, 16, 23, 32, 41, 64];
+
+const EAC_MODIFIERS: [[i32; 8]; 16] = [
+    [-3, -6, -9, -15, 2, 5, 8, 14],
+    [-3, -7, -10, -13, 2, 6, 9, 12],
+    [-2, -5, -8, -13, 1, 4, 7, 12],
+    [-2, -4, -6, -13, 1, 3, 5, 12],
+    [-3, -6, -8, -12, 2, 5, 7, 11],
+    [-3, -7, -9, -11, 2, 6, 8, 10],
+    [-4, -7, -8, -11, 3, 6, 7, 10],
+    [-3, -5, -8, -11, 2, 4, 7, 10],
+    [-2, -6, -8, -10, 1, 5, 7, 9],
+    [-2, -5, -8, -10, 1, 4, 7, 9],
+    [-2, -4, -8, -10, 1, 3, 7, 9],
+    [-2, -5, -7, -10, 1, 4, 6, 9],
+    [-3, -4, -7, -10, 2, 3, 6, 9],
+    [-1, -2, -3, -10, 0, 1, 2, 9],
+    [-4, -6, -8, -9, 3, 5, 7, 8],
+    [-3, -5, -7, -9, 2, 4, 6, 8],
+];
+
+fn expand4(value: u32) -> i32
+{
+    ((value << 4) | value) as i32
+}
+
+fn expand5(value: u32) -> i32
+{
+    ((value << 3) | (value >> 2)) as i32
+}
+
+fn clamp_u8(value: i32) -> u8
+{
+    value.clamp(0, 255) as u8
+}
+
+/// 纹素(x, y)的2位索引，ETC的纹素按列排列
+fn etc_index(low: u32, x: usize, y: usize) -> usize
+{
+    let p = x * 4 + y;
+    ((((low >> (16 + p)) & 1) << 1) | ((low >> p) & 1)) as usize
+}
+
+/// ETC2的RGB部分，包括兼容ETC1的单独和差分模式，以及T、H、平面模式
+fn decode_etc2_rgb(data: &[u8], out: &mut Rgba8Block)
+{
+    let high = u32::from_be_bytes([data[0], data[1], data[2], data[3]]);
+    let low = u32::from_be_bytes([data[4], data[5], data[6], data[7]]);
+
+    let bits = |shift: u32, count: u32| (high >> shift) & ((1 << count) - 1);
+
+    if bits(1, 1) == 0
+    {
+        let base1 = [expand4(bits(28, 4)), expand4(bits(20, 4)), expand4(bits(12, 4))];
+        let base2 = [expand4(bits(24, 4)), expand4(bits(16, 4)), expand4(bits(8, 4))];
+
+        decode_etc1_subblocks(high, low, base1, base2, out);
+        return;
+    }
+
+    //3位有符号的差值
+    let delta = |shift: u32| ((bits(shift, 3) << 29) as i32) >> 29;
+
+    let (r, g, b) = (bits(27, 5) as i32, bits(19, 5) as i32, bits(11, 5) as i32);
+    let (r2, g2, b2) = (r + delta(24), g + delta(16), b + delta(8));
+
+    if !(0..32).contains(&r2)
+    {
+        //T模式
+        let c1 = [expand4((bits(27, 2) << 2) | bits(24, 2)), expand4(bits(20, 4)), expand4(bits(16, 4))];
+        let c2 = [expand4(bits(12, 4)), expand4(bits(8, 4)), expand4(bits(4, 4))];
+        let d = ETC2_DISTANCES[((bits(2, 2) << 1) | bits(0, 1)) as usize];
+
+        let paint = [c1, c2.map(|c| c + d), c2, c2.map(|c| c - d)];
+        decode_etc2_paint(low, &paint, out);
+    }
+    else if !(0..32).contains(&g2)
+    {
+        //H模式
+        let c1 = [bits(27, 4), (bits(24, 3) << 1) | bits(20, 1), (bits(19, 1) << 3) | bits(15, 3)];
+        let c2 = [bits(11, 4), bits(7, 4), bits(3, 4)];
+
+        let value = |c: [u32; 3]| (c[0] << 8) | (c[1] << 4) | c[2];
+        let d = ETC2_DISTANCES[((bits(2, 1) << 2) | (bits(0, 1) << 1) | (value(c1) >= value(c2)) as u32) as usize];
+
+        let (c1, c2) = (c1.map(expand4), c2.map(expand4));
+        let paint = [c1.map(|c| c + d), c1.map(|c| c - d), c2.map(|c| c + d), c2.map(|c| c - d)];
+        decode_etc2_paint(low, &paint, out);
+    }
+    else if !(0..32).contains(&b2)
+    {
+        //平面模式
+        let expand6 = |v: u32| ((v << 2) | (v >> 4)) as i32;
+        let expand7 = |v: u32| ((v << 1) | (v >> 6)) as i32;
+
+        let o = [expand6(bits(25, 6)), expand7((bits(24, 1) << 6) | bits(17, 6)), expand6((bits(16, 1) << 5) | (bits(11, 2) << 3) | bits(7, 3))];
+        let h = [expand6((bits(2, 5) << 1) | bits(0, 1)), expand7((low >> 25) & 0x7F), expand6((low >> 19) & 0x3F)];
+        let v = [expand6((low >> 13) & 0x3F), expand7((low >> 6) & 0x7F), expand6(low & 0x3F)];
+
+        for (i, texel) in out.iter_mut().enumerate()
+        {
+            let (x, y) = ((i % 4) as i32, (i / 4) as i32);
+
+            for c in 0..3
+            {
+                texel[c] = clamp_u8((x * (h[c] - o[c]) + y * (v[c] - o[c]) + 4 * o[c] + 2) >> 2);
+            }
+        }
+    }
+    else
+    {
+        let base1 = [expand5(r as u32), expand5(g as u32), expand5(b as u32)];
+        let base2 = [expand5(r2 as u32), expand5(g2 as u32), expand5(b2 as u32)];
+
+        decode_etc1_subblocks(high, low, base1, base2, out);
+    }
+}
+
+/// 单独和差分模式，块被分成两个2x4或者4x2的子块，每个子块一个基础颜色和修正表
+fn decode_etc1_subblocks(high: u32, low: u32, base1: [i32; 3], base2: [i32; 3], out: &mut Rgba8Block)
+{
+    let flip = high & 1 == 1;
+    let tables = [(high >> 5) & 7, (high >> 2) & 7];
+
+    for (i, texel) in out.iter_mut().enumerate()
+    {
+        let (x, y) = (i % 4, i / 4);
+        let second = if flip { y >= 2 } else { x >= 2 };
+
+        let base = if second { base2 } else { base1 };
+        let modifiers = ETC1_MODIFIERS[tables[second as usize] as usize];
+
+        let modifier = match etc_index(low, x, y)
+        {
+            0 => modifiers[0],
+            1 => modifiers[1],
+            2 => -modifiers[0],
+            _ => -modifiers[1],
+        };
+
+        for c in 0..3
+        {
+            texel[c] = clamp_u8(base[c] + modifier);
+        }
+    }
+}
+
+/// T和H模式，索引直接选择4种颜色中的一种
+fn decode_etc2_paint(low: u32, paint: &[[i32; 3]; 4], out: &mut Rgba8Block)
+{
+    for (i, texel) in out.iter_mut().enumerate()
+    {
+        let color = paint[etc_index(low, i % 4, i / 4)];
+
+        for c in 0..3
+        {
+            texel[c] = clamp_u8(color[c]);
+        }
+    }
+}
+
+/// ETC2 RGBA8的alpha部分
+fn decode_eac_alpha(data: &[u8], out: &mut Rgba8Block)
+{
+    let bits = u64::from_be_bytes(data[0..8].try_into().unwrap());
+
+    let base = (bits >> 56) as i32;
+    let multiplier = ((bits >> 52) & 0xF) as i32;
+    let modifiers = EAC_MODIFIERS[((bits >> 48) & 0xF) as usize];
+
+    for p in 0..16
+    {
+        let index = ((bits >> (45 - 3 * p)) & 7) as usize;
+        let (x, y) = (p / 4, p % 4);
+
+        out[y * 4 + x][3] = clamp_u8(base + modifiers[index] * multiplier);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,103 @@
+use glam::{UVec4, Vec4};
+
+use crate::gl::{enums::glTextureFormat::GLTextureFormat, util::{f16_to_f32, f32_to_f16, pack_r11g11b10f, unpack_r11g11b10f, srgb_to_linear, linear_to_srgb}};
+
+fn read_u16(bytes: &[u8], i: usize) -> u16
+{
+    u16::from_ne_bytes([bytes[i * 2], bytes[i * 2 + 1]])
+}
+
+fn read_u32(bytes: &[u8], i: usize) -> u32
+{
+    u32::from_ne_bytes([bytes[i * 4], bytes[i * 4 + 1], bytes[i * 4 + 2], bytes[i * 4 + 3]])
+}
+
+fn read_f32(bytes: &[u8], i: usize) -> f32
+{
+    f32::from_bits(read_u32(bytes, i))
+}
+
+fn unorm8(value: f32) -> u8
+{
+    (value.clamp(0., 1.) * 255. + 0.5) as u8
+}
+
+/// 把一个纹素解码为Vec4，缺少的颜色通道是0，alpha是1。sRGB格式返回线性值，整数格式返回没有归一化的原始值
+pub(super) fn decode_texel(format: GLTextureFormat, bytes: &[u8]) -> Vec4
+{
+    const INV_255: f32 = 1. / 255.;
+
+    match format
+    {
+        GLTextureFormat::R8 => Vec4::new(bytes[0] as f32 * INV_255, 0., 0., 1.),
+        GLTextureFormat::RG8 => Vec4::new(bytes[0] as f32 * INV_255, bytes[1] as f32 * INV_255, 0., 1.),
+        GLTextureFormat::RGB8 => Vec4::new(bytes[0] as f32, bytes[1] as f32, bytes[2] as f32, 255.) * INV_255,
+        GLTextureFormat::RGBA8 => Vec4::new(bytes[0] as f32, bytes[1] as f32, bytes[2] as f32, bytes[3] as f32) * INV_255,
+        GLTextureFormat::SRGB8 => Vec4::new(srgb_to_linear(bytes[0]), srgb_to_linear(bytes[1]), srgb_to_linear(bytes[2]), 1.),
+        GLTextureFormat::SRGB8A8 => Vec4::new(srgb_to_linear(bytes[0]), srgb_to_linear(bytes[1]), srgb_to_linear(bytes[2]), bytes[3] as f32 * INV_255),
+        GLTextureFormat::RGBA16F => Vec4::new(f16_to_f32(read_u16(bytes, 0)), f16_to_f32(read_u16(bytes, 1)),
+            f16_to_f32(read_u16(bytes, 2)), f16_to_f32(read_u16(bytes, 3))),
+        GLTextureFormat::R32F => Vec4::new(read_f32(bytes, 0), 0., 0., 1.),
+        GLTextureFormat::RGBA32F => Vec4::new(read_f32(bytes, 0), read_f32(bytes, 1), read_f32(bytes, 2), read_f32(bytes, 3)),
+        GLTextureFormat::R11G11B10F =>
+        {
+            let (r, g, b) = unpack_r11g11b10f(read_u32(bytes, 0));
+            Vec4::new(r, g, b, 1.)
+        }
+        GLTextureFormat::D16 => Vec4::new(read_u16(bytes, 0) as f32 * (1. / 65535.), 0., 0., 1.),
+        GLTextureFormat::D32F => Vec4::new(read_f32(bytes, 0), 0., 0., 1.),
+        GLTextureFormat::R32UI | GLTextureFormat::RGBA8UI => decode_uint(format, bytes).as_vec4(),
+        //压缩格式按块解码，见compressed.rs
+        _ => unreachable!(),
+    }
+}
+
+/// 整数格式的原始值，R32UI缺少的通道是0，alpha是1
+pub(super) fn decode_uint(format: GLTextureFormat, bytes: &[u8]) -> UVec4
+{
+    match format
+    {
+        GLTextureFormat::R32UI => UVec4::new(read_u32(bytes, 0), 0, 0, 1),
+        GLTextureFormat::RGBA8UI => UVec4::new(bytes[0] as u32, bytes[1] as u32, bytes[2] as u32, bytes[3] as u32),
+        _ => decode_texel(format, bytes).as_uvec4(),
+    }
+}
+
+/// 把Vec4编码为一个纹素写入bytes，定点格式会被限制在[0, 1]内并且四舍五入，sRGB格式的输入是线性值
+pub(super) fn encode_texel(format: GLTextureFormat, value: Vec4, bytes: &mut [u8])
+{
+    fn write<const N: usize>(bytes: &mut [u8], i: usize, value: [u8; N])
+    {
+        bytes[i * N..(i + 1) * N].copy_from_slice(&value);
+    }
+
+    match format
+    {
+        GLTextureFormat::R8 => bytes[0] = unorm8(value.x),
+        GLTextureFormat::RG8 => write(bytes, 0, [unorm8(value.x), unorm8(value.y)]),
+        GLTextureFormat::RGB8 => write(bytes, 0, [unorm8(value.x), unorm8(value.y), unorm8(value.z)]),
+        GLTextureFormat::RGBA8 => write(bytes, 0, [unorm8(value.x), unorm8(value.y), unorm8(value.z), unorm8(value.w)]),
+        GLTextureFormat::SRGB8 => write(bytes, 0, [linear_to_srgb(value.x), linear_to_srgb(value.y), linear_to_srgb(value.z)]),
+        GLTextureFormat::SRGB8A8 => write(bytes, 0, [linear_to_srgb(value.x), linear_to_srgb(value.y), linear_to_srgb(value.z), unorm8(value.w)]),
+        GLTextureFormat::RGBA16F =>
+        {
+            for (i, v) in value.to_array().into_iter().enumerate()
+            {
+                write(bytes, i, f32_to_f16(v).to_ne_bytes());
+            }
+        }
+        GLTextureFormat::R32F | GLTextureFormat::D32F => write(bytes, 0, value.x.to_ne_bytes()),
+        GLTextureFormat::RGBA32F =>
+        {
+            for (i, v) in value.to_array().into_iter().enumerate()
+            {
+                write(bytes, i, v.to_ne_bytes());
+            }
+        }
+        GLTextureFormat::R11G11B10F => write(bytes, 0, pack_r11g11b10f(value.x, value.y, value.z).to_ne_bytes()),
+        GLTextureFormat::D16 => write(bytes, 0, ((value.x.clamp(0., 1.) * 65535. + 0.5) as u16).to_ne_bytes()),
+        GLTextureFormat::R32UI => write(bytes, 0, (value.x as u32).to_ne_bytes()),
+        GLTextureFormat::RGBA8UI => write(bytes, 0, [value.x as u8, value.y as u8, value.z as u8, value.w as u8]),
+        _ => unreachable!(),
+    }
+}
\ No newline at end of file
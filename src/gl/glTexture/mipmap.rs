@@ -0,0 +1,352 @@
+use glam::Vec4;
+
+use crate::gl::{glColor::GLColor, enums::{glMipmapFilter::GLMipmapFilter, glTextureFormat::GLTextureFormat}, util::{srgb_to_linear, linear_to_srgb}};
+
+use super::{GLTexture, format::encode_texel};
+
+impl GLTexture
+{
+    /// 生成level层mipmap，None时一直生成到1x1，使用的滤波器由set_mipmap_filter等函数设置。
+    /// 每一层的长宽是上一层的一半向下取整，任意长宽都可以生成完整的mipmap链。空纹理、整数格式和压缩格式的纹理会返回false
+    pub fn create_mipmap(&mut self, level: Option<u32>) -> bool
+    {
+        if self.width == 0 || self.height == 0 || self.format.is_integer() || self.format.is_compressed()
+        {
+            return false;
+        }
+
+        let level = level.unwrap_or(u32::MAX);
+
+        self.mipmaps.clear();
+
+        if self.format == GLTextureFormat::RGBA8 && self.mipmap_filter == GLMipmapFilter::Box && !self.mipmap_srgb && self.alpha_cutoff.is_none()
+        {
+            self.create_mipmap_inner(self.width, self.height, level);
+        }
+        else
+        {
+            self.create_mipmap_filtered(level);
+        }
+
+        true
+    }
+
+    fn create_mipmap_inner(&mut self, width: usize, height: usize, level: u32)
+    {
+        if (width == 1 && height == 1) || level == 0
+        {
+            return;
+        }
+
+        let last_mipmap = &self.mipmaps.last().unwrap_or(self).data;
+        let colors = unsafe { std::slice::from_raw_parts(last_mipmap.as_ptr() as *const GLColor, last_mipmap.len() / 4) };
+
+        let new_width = usize::max(width / 2, 1);
+        let new_height = usize::max(height / 2, 1);
+
+        let mut result: Vec<GLColor> = Vec::with_capacity(new_width * new_height);
+
+        for i in 0..new_height
+        {
+            let (y, y_weights, y_count) = mip_taps(height, i);
+
+            for j in 0..new_width
+            {
+                let (x, x_weights, x_count) = mip_taps(width, j);
+                let mut sum = [0u64; 4];
+
+                //权重都是整数，用整数累加避免奇数长度时误差一层层累积。奇数长度的总权重是长乘宽，需要用u64
+                for dy in 0..y_count
+                {
+                    for dx in 0..x_count
+                    {
+                        let color = colors[(y + dy) * width + x + dx];
+                        let weight = x_weights[dx] as u64 * y_weights[dy] as u64;
+
+                        sum[0] += color.r as u64 * weight;
+                        sum[1] += color.g as u64 * weight;
+                        sum[2] += color.b as u64 * weight;
+                        sum[3] += color.a as u64 * weight;
+                    }
+                }
+
+                //四舍五入，直接截断会让每一层都偏暗
+                let total = mip_weight_sum(width) as u64 * mip_weight_sum(height) as u64;
+                let [r, g, b, a] = sum.map(|c| ((c + total / 2) / total) as u8);
+                result.push(GLColor::new(r, g, b, a));
+            }
+        }
+
+        self.mipmaps.push(Self::from_colors(result, new_width, new_height).unwrap());
+
+        self.create_mipmap_inner(new_width, new_height, level - 1);
+    }
+
+    /// 在浮点数的线性空间中滤波，每一层都从上一层的浮点结果缩小，不会累积量化误差。
+    /// 只有8位定点格式会按照sRGB处理
+    fn create_mipmap_filtered(&mut self, level: u32)
+    {
+        let format = self.format;
+        let srgb = self.mipmap_srgb && matches!(format, GLTextureFormat::R8 | GLTextureFormat::RG8 | GLTextureFormat::RGB8 | GLTextureFormat::RGBA8);
+        let (mut width, mut height) = (self.width, self.height);
+
+        let mut current: Vec<Vec4> = (0..width * height).map(|i| decode(self.get_texel_at(i), srgb)).collect();
+        let coverage = self.alpha_cutoff.map(|cutoff| (cutoff, alpha_coverage(&current, format, cutoff, 1.)));
+
+        for _ in 0..level
+        {
+            if width == 1 && height == 1
+            {
+                break;
+            }
+
+            let new_width = usize::max(width / 2, 1);
+            let new_height = usize::max(height / 2, 1);
+
+            current = resample(&current, (width, height), (new_width, new_height), self.mipmap_filter);
+            (width, height) = (new_width, new_height);
+
+            //缩放alpha只影响保存的这一层，下一层仍然从没有缩放的结果生成
+            let alpha_scale = coverage.map_or(1., |(cutoff, target)| coverage_scale(&current, format, cutoff, target));
+            let mut data = vec![0u8; width * height * format.size()];
+
+            for (value, texel) in current.iter().zip(data.chunks_exact_mut(format.size()))
+            {
+                encode_texel(format, encode(*value, alpha_scale, srgb), texel);
+            }
+
+            self.mipmaps.push(Self::from_data(data, width, height, format).unwrap());
+        }
+    }
+}
+
+/// 缩小到一半时，第i个像素在长度为size的轴上使用的起始源像素、权重以及像素数量。
+/// 偶数长度取2个像素的平均值，奇数长度用3个像素的多相滤波，保证每个源像素的总权重相同
+fn mip_taps(size: usize, i: usize) -> (usize, [u32; 3], usize)
+{
+    if size == 1
+    {
+        (0, [1, 0, 0], 1)
+    }
+    else if size % 2 == 0
+    {
+        (i * 2, [1, 1, 0], 2)
+    }
+    else
+    {
+        let n = (size / 2) as u32;
+        (i * 2, [n - i as u32, n, i as u32 + 1], 3)
+    }
+}
+
+/// mip_taps返回的权重之和
+fn mip_weight_sum(size: usize) -> u32
+{
+    if size == 1 { 1 } else if size % 2 == 0 { 2 } else { size as u32 }
+}
+
+fn decode(value: Vec4, srgb: bool) -> Vec4
+{
+    if srgb
+    {
+        let linear = |v: f32| srgb_to_linear((v * 255. + 0.5) as u8);
+        Vec4::new(linear(value.x), linear(value.y), linear(value.z), value.w)
+    }
+    else
+    {
+        value
+    }
+}
+
+/// 缩放alpha并且转换回sRGB，之后由encode_texel量化，定点格式会限制负瓣产生的超出[0, 1]的值
+fn encode(value: Vec4, alpha_scale: f32, srgb: bool) -> Vec4
+{
+    let a = value.w * alpha_scale;
+
+    if srgb
+    {
+        let encode = |v: f32| linear_to_srgb(v) as f32 * (1. / 255.);
+        Vec4::new(encode(value.x), encode(value.y), encode(value.z), a)
+    }
+    else
+    {
+        Vec4::new(value.x, value.y, value.z, a)
+    }
+}
+
+/// 滤波器在目标像素单位下的半径
+fn filter_radius(filter: GLMipmapFilter) -> f32
+{
+    match filter
+    {
+        GLMipmapFilter::Box => 0.5,
+        GLMipmapFilter::Kaiser | GLMipmapFilter::Lanczos => 3.,
+        GLMipmapFilter::Mitchell => 2.,
+    }
+}
+
+fn sinc(x: f32) -> f32
+{
+    if x.abs() < 1e-6
+    {
+        1.
+    }
+    else
+    {
+        let x = x * std::f32::consts::PI;
+        x.sin() / x
+    }
+}
+
+/// 第一类零阶修正贝塞尔函数，Kaiser窗使用
+fn bessel_i0(x: f32) -> f32
+{
+    let mut sum = 1.;
+    let mut term = 1.;
+    let mut k = 1.;
+
+    while term > sum * 1e-7
+    {
+        term *= (x * 0.5 / k) * (x * 0.5 / k);
+        sum += term;
+        k += 1.;
+    }
+
+    sum
+}
+
+fn filter_kernel(filter: GLMipmapFilter, x: f32) -> f32
+{
+    const KAISER_ALPHA: f32 = 4.;
+    //Mitchell-Netravali推荐的B = C = 1/3
+    const B: f32 = 1. / 3.;
+    const C: f32 = 1. / 3.;
+
+    let x = x.abs();
+    let radius = filter_radius(filter);
+
+    if x >= radius
+    {
+        return 0.;
+    }
+
+    match filter
+    {
+        GLMipmapFilter::Box => 1.,
+        GLMipmapFilter::Kaiser =>
+        {
+            let t = x / radius;
+            sinc(x) * bessel_i0(KAISER_ALPHA * (1. - t * t).sqrt()) / bessel_i0(KAISER_ALPHA)
+        }
+        GLMipmapFilter::Lanczos => sinc(x) * sinc(x / radius),
+        GLMipmapFilter::Mitchell if x < 1. =>
+        {
+            ((12. - 9. * B - 6. * C) * x * x * x + (-18. + 12. * B + 6. * C) * x * x + (6. - 2. * B)) / 6.
+        }
+        GLMipmapFilter::Mitchell =>
+        {
+            ((-B - 6. * C) * x * x * x + (6. * B + 30. * C) * x * x + (-12. * B - 48. * C) * x + (8. * B + 24. * C)) / 6.
+        }
+    }
+}
+
+/// 一个轴上每个目标像素使用的源像素和归一化之后的权重
+fn axis_weights(size: usize, new_size: usize, filter: GLMipmapFilter) -> Vec<Vec<(usize, f32)>>
+{
+    if size == new_size
+    {
+        return (0..size).map(|i| vec![(i, 1.)]).collect();
+    }
+
+    (0..new_size).map(|i|
+    {
+        let mut taps = Vec::new();
+
+        if filter == GLMipmapFilter::Box
+        {
+            //和整数版本一样使用多相滤波
+            let (start, weights, count) = mip_taps(size, i);
+
+            taps.extend(weights[..count].iter().enumerate().map(|(k, &weight)| (start + k, weight as f32)));
+        }
+        else
+        {
+            let scale = size as f32 / new_size as f32;
+            let center = (i as f32 + 0.5) * scale;
+            let support = filter_radius(filter) * scale;
+
+            for k in (center - support).floor() as i32..=(center + support).ceil() as i32
+            {
+                let weight = filter_kernel(filter, (k as f32 + 0.5 - center) / scale);
+
+                if weight != 0.
+                {
+                    taps.push((k.clamp(0, size as i32 - 1) as usize, weight));
+                }
+            }
+        }
+
+        let sum: f32 = taps.iter().map(|tap| tap.1).sum();
+        taps.iter().map(|&(k, weight)| (k, weight / sum)).collect()
+    }).collect()
+}
+
+/// 先水平再竖直的可分离滤波
+fn resample(src: &[Vec4], (width, height): (usize, usize), (new_width, new_height): (usize, usize), filter: GLMipmapFilter) -> Vec<Vec4>
+{
+    let x_weights = axis_weights(width, new_width, filter);
+    let y_weights = axis_weights(height, new_height, filter);
+
+    let mut temp = Vec::with_capacity(new_width * height);
+
+    for y in 0..height
+    {
+        for taps in &x_weights
+        {
+            temp.push(taps.iter().fold(Vec4::ZERO, |sum, &(x, weight)| sum + src[y * width + x] * weight));
+        }
+    }
+
+    let mut result = Vec::with_capacity(new_width * new_height);
+
+    for taps in &y_weights
+    {
+        for x in 0..new_width
+        {
+            result.push(taps.iter().fold(Vec4::ZERO, |sum, &(y, weight)| sum + temp[y * new_width + x] * weight));
+        }
+    }
+
+    result
+}
+
+/// alpha乘上scale并且按照格式量化之后大于cutoff的像素比例
+fn alpha_coverage(colors: &[Vec4], format: GLTextureFormat, cutoff: f32, scale: f32) -> f32
+{
+    let quantize = |a: f32| if format.is_float() { a } else { ((a.clamp(0., 1.) * 255. + 0.5) as u8) as f32 * (1. / 255.) };
+    let covered = colors.iter().filter(|color| quantize(color.w * scale) > cutoff).count();
+    covered as f32 / colors.len() as f32
+}
+
+/// 二分查找让覆盖率最接近target的alpha缩放
+fn coverage_scale(colors: &[Vec4], format: GLTextureFormat, cutoff: f32, target: f32) -> f32
+{
+    let (mut min, mut max) = (0f32, 4f32);
+
+    for _ in 0..16
+    {
+        let mid = (min + max) * 0.5;
+
+        if alpha_coverage(colors, format, cutoff, mid) < target
+        {
+            min = mid;
+        }
+        else
+        {
+            max = mid;
+        }
+    }
+
+    //覆盖率是离散的，可能达不到target，取两边更接近的一个
+    if target - alpha_coverage(colors, format, cutoff, min) < alpha_coverage(colors, format, cutoff, max) - target { min } else { max }
+}
\ No newline at end of file
@@ -6,4 +6,5 @@ pub mod sampler;
 pub mod glColor;
 pub mod enums;
 pub mod glFrameBuffer;
-pub mod util;
\ No newline at end of file
+pub mod util;
+pub mod glIndex;
\ No newline at end of file
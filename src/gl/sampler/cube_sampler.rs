@@ -2,11 +2,11 @@
 
 use std::sync::Arc;
 
-use glam::{Vec3, Vec2};
+use glam::{Vec3, Vec2, Vec4};
 
-use crate::{gl::{glTexture::GLTexture, glColor::GLColor}, gl::{util::{log2, find_max16_power2, sqrt, inv_sqrt, find_log2_max16}}};
+use crate::{gl::{glTexture::GLTexture, glColor::GLColor, enums::glTextureFormat::GLTextureFormat}, gl::{util::{log2, find_max16_power2, sqrt, inv_sqrt, find_log2_max16}}};
 
-use super::{GLFilterFunc, sampler::Sampler, bilerp, isotropic_min_filter, anisotropic_min_filter};
+use super::{GLFilterFunc, sampler::Sampler, bilerp, bilerp_f, isotropic_min_filter, isotropic_min_filter_f, anisotropic_min_filter, anisotropic_min_filter_f};
 
 #[derive(Default, PartialEq, Clone, Copy)]
 enum Face
@@ -247,6 +247,11 @@ impl CubeSampler
         let texture = &self.textures.as_ref().unwrap()[self.sampled_pixels_face[self.sampled_idx] as usize];
         let uv = self.sampled_pixels_uv[self.sampled_idx];
 
+        if texture.get_format() != GLTextureFormat::RGBA8
+        {
+            return self.get_vec4().into();
+        }
+
         let color;
 
         if self.long_level <= 0.
@@ -281,4 +286,49 @@ impl CubeSampler
         self.sampled_idx = if self.sampled_idx == 3 { 0 } else { self.sampled_idx + 1 };
         color
     }
+
+    /// 按照浮点数获取颜色，可以用于HDR环境贴图，概念同Sampler2D::get_vec4
+    pub fn get_vec4(&mut self) -> Vec4
+    {
+        let texture = &self.textures.as_ref().unwrap()[self.sampled_pixels_face[self.sampled_idx] as usize];
+        let uv = self.sampled_pixels_uv[self.sampled_idx];
+
+        let color;
+
+        if texture.get_format().is_integer()
+        {
+            color = texture.get_texel(texture.compute_st(uv) + 0.5)
+        }
+        else if self.long_level <= 0.
+        {
+            match self.mag_filter
+            {
+                GLFilterFunc::Nearest =>
+                {
+                    color = texture.get_texel(texture.compute_st(uv) + 0.5)
+                }
+
+                GLFilterFunc::Linear =>
+                {
+                    color = bilerp_f(texture.compute_st(uv), texture)
+                }
+
+                _ => unreachable!()
+            }
+        }
+        else
+        {
+            if self.sample_point == 1
+            {
+                color = isotropic_min_filter_f(self, uv, texture)
+            }
+            else
+            {
+                color = anisotropic_min_filter_f(self, uv, texture)
+            }
+        }
+
+        self.sampled_idx = if self.sampled_idx == 3 { 0 } else { self.sampled_idx + 1 };
+        color
+    }
 }
\ No newline at end of file
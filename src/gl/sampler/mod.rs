@@ -1,4 +1,4 @@
-use glam::Vec2;
+use glam::{Vec2, Vec4};
 
 use crate::gl::util::find_log2_max16;
 
@@ -262,6 +262,118 @@ fn anisotropic_min_filter<S: Sampler>(sampler: &S, uv: Vec2, texture: &GLTexture
     (a << 24 | b << 16 | g << 8 | r).into()
 }
 
+/// 浮点版本的双线性插值，用于RGBA8以外的格式
+fn bilerp_f(st: Vec2, texture: &GLTexture) -> Vec4
+{
+    let diff = st - st.floor();
+
+    let xp1 = (st.x + 1.).min(texture.width as f32 - 1.);
+    let yp1 = (st.y + 1.).min(texture.height as f32 - 1.);
+
+    let this = texture.get_texel(st);
+    let side = texture.get_texel(Vec2::new(xp1, st.y));
+    let up = texture.get_texel(Vec2::new(st.x, yp1));
+    let diagonal = texture.get_texel(Vec2::new(xp1, yp1));
+
+    this.lerp(side, diff.x).lerp(up.lerp(diagonal, diff.x), diff.y)
+}
+
+/// 和isotropic_min_filter相同，结果是浮点数
+fn isotropic_min_filter_f<S: Sampler>(sampler: &S, uv: Vec2, texture: &GLTexture) -> Vec4
+{
+    let level = sampler.get_long_level();
+
+    match sampler.get_min_filter()
+    {
+        GLFilterFunc::NearestMipmapNearest =>
+        {
+            let tex = texture.get_mipmap(level.round());
+            tex.get_texel(tex.compute_st(uv) + 0.5)
+        }
+
+        GLFilterFunc::LinearMipmapNearest =>
+        {
+            let tex = texture.get_mipmap(level.round());
+            bilerp_f(tex.compute_st(uv), tex)
+        }
+
+        GLFilterFunc::NearestMipmapLinear =>
+        {
+            let left  = texture.get_mipmap(level.floor());
+            let right = texture.get_mipmap(level.ceil());
+
+            left.get_texel(left.compute_st(uv)).lerp(right.get_texel(right.compute_st(uv)), level - level.floor())
+        }
+
+        GLFilterFunc::LinearMipmapLinear =>
+        {
+            let left  = texture.get_mipmap(level.floor());
+            let right = texture.get_mipmap(level.ceil());
+
+            bilerp_f(left.compute_st(uv), left).lerp(bilerp_f(right.compute_st(uv), right), level - level.floor())
+        }
+
+        _ => unreachable!()
+    }
+}
+
+/// 和anisotropic_min_filter相同，结果是浮点数
+fn anisotropic_min_filter_f<S: Sampler>(sampler: &S, uv: Vec2, texture: &GLTexture) -> Vec4
+{
+    let level = sampler.get_aniso_level();
+    let sample_point = sampler.get_sample_point();
+    let offset = |i| sampler.get_ddxy() * get_offset(sample_point, i);
+
+    let mut sum = Vec4::ZERO;
+
+    match sampler.get_min_filter()
+    {
+        GLFilterFunc::NearestMipmapNearest | GLFilterFunc::NearestMipmapLinear =>
+        {
+            let tex = texture.get_mipmap(level.round());
+            let footprint_center = tex.compute_st(uv);
+            let linear = sampler.get_min_filter() == GLFilterFunc::NearestMipmapLinear;
+
+            for i in 0..sample_point
+            {
+                let st = footprint_center + offset(i);
+                sum += if linear { bilerp_f(st, tex) } else { tex.get_texel(st) };
+            }
+        }
+
+        GLFilterFunc::LinearMipmapNearest | GLFilterFunc::LinearMipmapLinear =>
+        {
+            let left  = texture.get_mipmap(level.floor());
+            let right = texture.get_mipmap(level.ceil());
+
+            let footprint_center_left = left.compute_st(uv);
+            let footprint_center_right = right.compute_st(uv);
+            let linear = sampler.get_min_filter() == GLFilterFunc::LinearMipmapLinear;
+
+            for i in 0..sample_point
+            {
+                let st_left = footprint_center_left + offset(i);
+                let st_right = footprint_center_right + offset(i);
+
+                let (c_left, c_right) = if linear
+                {
+                    (bilerp_f(st_left, left), bilerp_f(st_right, right))
+                }
+                else
+                {
+                    (left.get_texel(st_left), right.get_texel(st_right))
+                };
+
+                sum += c_left.lerp(c_right, level - level.floor());
+            }
+        }
+
+        _ => unreachable!(),
+    }
+
+    sum / sample_point as f32
+}
+
 fn get_offset(sample_point: i32, i: i32) -> f32
 {
     const LUT: [f32; 32] = [
@@ -1,10 +1,10 @@
 use std::sync::Arc;
 
-use glam::Vec2;
+use glam::{UVec4, Vec2, Vec4};
 
-use crate::{gl::util::{find_max16_power2, log2, sqrt, inv_sqrt, find_log2_max16}, gl::{glTexture::GLTexture, glColor::GLColor}};
+use crate::{gl::util::{find_max16_power2, log2, sqrt, inv_sqrt, find_log2_max16}, gl::{glTexture::GLTexture, glColor::GLColor, enums::glTextureFormat::GLTextureFormat}};
 
-use super::{WrapMode, GLFilterFunc, wrap, bilerp, isotropic_min_filter, sampler::Sampler, anisotropic_min_filter};
+use super::{WrapMode, GLFilterFunc, wrap, bilerp, bilerp_f, isotropic_min_filter, isotropic_min_filter_f, sampler::Sampler, anisotropic_min_filter, anisotropic_min_filter_f};
 
 #[derive(Default, Clone)]
 pub struct Sampler2D
@@ -172,12 +172,17 @@ impl Sampler2D
         self.aniso_level = self.long_level - find_log2_max16(self.sample_point) as f32;
     }
 
-    ///获取颜色，根据采样器的设置进行过滤
+    ///获取颜色，根据采样器的设置进行过滤，RGBA8以外的格式会先按照浮点数过滤再转换
     pub fn get_color(&mut self) -> GLColor
     {
         let uv = self.sampled_pixels_uv[self.sampled_idx];
         let texture = self.texture.as_ref().unwrap();
 
+        if texture.get_format() != GLTextureFormat::RGBA8
+        {
+            return self.get_vec4().into();
+        }
+
         let color;
 
         if self.long_level <= 0.
@@ -213,6 +218,64 @@ impl Sampler2D
         color
     }
 
+    /// 按照浮点数获取颜色，浮点格式不会损失精度，也不会被限制在[0, 1]内。
+    /// 缺少的颜色通道是0，alpha是1，深度纹理的深度在x中，整数格式只做临近采样并返回没有归一化的值
+    pub fn get_vec4(&mut self) -> Vec4
+    {
+        let uv = self.sampled_pixels_uv[self.sampled_idx];
+        let texture = self.texture.as_ref().unwrap();
+
+        let color;
+
+        if texture.get_format().is_integer()
+        {
+            color = texture.get_texel(texture.compute_st(uv) + 0.5)
+        }
+        else if self.long_level <= 0.
+        {
+            match self.mag_filter
+            {
+                GLFilterFunc::Nearest =>
+                {
+                    color = texture.get_texel(texture.compute_st(uv) + 0.5)
+                }
+
+                GLFilterFunc::Linear =>
+                {
+                    color = bilerp_f(texture.compute_st(uv), texture)
+                }
+
+                _ => unreachable!()
+            }
+        }
+        else
+        {
+            if self.sample_point == 1
+            {
+                color = isotropic_min_filter_f(self, uv, texture)
+            }
+            else
+            {
+                color = anisotropic_min_filter_f(self, uv, texture)
+            }
+        }
+
+        self.sampled_idx = if self.sampled_idx == 3 { 0 } else { self.sampled_idx + 1 };
+        color
+    }
+
+    /// 读取整数格式纹理第0层的原始值，总是临近采样
+    pub fn get_uvec4(&mut self) -> UVec4
+    {
+        let uv = self.sampled_pixels_uv[self.sampled_idx];
+        let texture = self.texture.as_ref().unwrap();
+
+        let color = texture.get_uint(texture.compute_st(uv) + 0.5);
+
+        self.sampled_idx = if self.sampled_idx == 3 { 0 } else { self.sampled_idx + 1 };
+        color
+    }
+
     fn wrap(&self, mut uv: Vec2) -> Vec2
     {
         uv.x = wrap(uv.x, self.wrap_s);
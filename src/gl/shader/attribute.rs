@@ -39,6 +39,21 @@ impl<R: Clone + Copy, T: Index<usize, Output = R> + Default> Attribute<R, T>
         }
     }
 
+    /// 相当于调用n次forward
+    pub fn forward_n(&mut self, n: usize)
+    {
+        if self.forward_every_n_iter == 0
+        {
+            return;
+        }
+
+        let every = self.forward_every_n_iter as usize;
+        let total = self.n as usize + n;
+
+        self.idx += total / every;
+        self.n = (total % every) as u32;
+    }
+
     pub fn get(&self) -> R
     {
         self.data[self.idx]
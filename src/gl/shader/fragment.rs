@@ -0,0 +1,119 @@
+use glam::Vec4;
+
+use crate::gl::{glColor::GLColor, glFrameBuffer::MAX_COLOR_ATTACHMENTS};
+
+/// 片段着色器输出的一个颜色，要写入超出[0, 1]的值到浮点格式的颜色附着时使用Float
+#[derive(Clone, Copy, Debug)]
+pub enum FragmentColor
+{
+    Unorm(GLColor),
+    Float(Vec4),
+}
+
+impl Default for FragmentColor
+{
+    fn default() -> Self
+    {
+        FragmentColor::Unorm(GLColor::ZERO)
+    }
+}
+
+impl FragmentColor
+{
+    pub fn to_color(&self) -> GLColor
+    {
+        match *self
+        {
+            FragmentColor::Unorm(color) => color,
+            FragmentColor::Float(color) => color.into(),
+        }
+    }
+
+    pub fn to_vec4(&self) -> Vec4
+    {
+        match *self
+        {
+            FragmentColor::Unorm(color) => color.into(),
+            FragmentColor::Float(color) => color,
+        }
+    }
+
+    /// alpha乘上覆盖率，给抗锯齿使用
+    pub(crate) fn apply_coverage(&mut self, coverage: f32)
+    {
+        match self
+        {
+            FragmentColor::Unorm(color) => color.a = (color.a as f32 * coverage) as u8,
+            FragmentColor::Float(color) => color.w *= coverage,
+        }
+    }
+}
+
+impl From<GLColor> for FragmentColor
+{
+    fn from(color: GLColor) -> Self
+    {
+        FragmentColor::Unorm(color)
+    }
+}
+
+impl From<Vec4> for FragmentColor
+{
+    fn from(color: Vec4) -> Self
+    {
+        FragmentColor::Float(color)
+    }
+}
+
+/// 片段着色器的完整输出，由Program::fragment_output返回
+#[derive(Clone, Copy, Default, Debug)]
+pub struct FragmentOutput
+{
+    /// 第i个颜色写入帧缓冲的第i个颜色附着，alpha测试使用第0个颜色
+    pub colors: [FragmentColor; MAX_COLOR_ATTACHMENTS],
+
+    /// 不为None时代替插值得到的深度(gl_FragDepth)，和深度缓冲中的值在同一个空间
+    pub depth: Option<f32>,
+
+    /// 为true时丢弃这个片段，不写入任何缓冲
+    pub discarded: bool,
+}
+
+impl FragmentOutput
+{
+    /// 只设置第0个颜色
+    pub fn new(color: GLColor) -> Self
+    {
+        let mut colors = [FragmentColor::default(); MAX_COLOR_ATTACHMENTS];
+        colors[0] = FragmentColor::Unorm(color);
+
+        Self { colors, depth: None, discarded: false }
+    }
+
+    /// 丢弃片段，相当于GLSL的discard
+    pub fn discard() -> Self
+    {
+        Self { discarded: true, ..Default::default() }
+    }
+
+    /// 设置第index个颜色，用于多渲染目标(MRT)，可以是GLColor或者Vec4
+    pub fn with_color(mut self, index: usize, color: impl Into<FragmentColor>) -> Self
+    {
+        self.colors[index] = color.into();
+        self
+    }
+
+    pub fn with_depth(mut self, depth: f32) -> Self
+    {
+        self.depth = Some(depth);
+        self
+    }
+}
+
+impl From<GLColor> for FragmentOutput
+{
+    fn from(color: GLColor) -> Self
+    {
+        Self::new(color)
+    }
+}
\ No newline at end of file
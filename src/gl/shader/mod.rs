@@ -2,3 +2,4 @@ pub mod program;
 pub mod varying;
 pub mod shader;
 pub mod attribute;
+pub mod fragment;
\ No newline at end of file
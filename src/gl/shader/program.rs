@@ -1,13 +1,41 @@
-use glam::{Vec4, IVec2};
+use glam::{Vec4, IVec2, Vec2};
 
 use crate::gl::glColor::GLColor;
 
-use super::varying::Varying;
+use super::{varying::Varying, fragment::FragmentOutput};
 
 pub trait Program<V: Varying>
 {
+    /// fragment_output可能丢弃片段时必须设为true，光栅化时会关闭early z。
+    /// 光栅化器不会自己检测，没有设置却丢弃了片段时，被丢弃的片段已经写入了深度和模板，debug模式下会panic
+    const DISCARD: bool = false;
+
+    /// fragment_output可能输出深度时必须设为true，光栅化时会关闭early z。
+    /// 没有设置却输出了深度时，输出的深度会被忽略，debug模式下会panic
+    const FRAGMENT_DEPTH: bool = false;
+
     fn vertex(&mut self, index: usize) -> Vec4;
     fn fragment(&mut self, varying: &V, pos: IVec2) -> GLColor;
 
     fn sample(&mut self, varying: &V);
+
+    /// 光栅化时实际调用的片段着色器，默认直接使用fragment的颜色。
+    /// 需要丢弃片段或者输出深度时重写这个函数，并且必须设置DISCARD或FRAGMENT_DEPTH
+    fn fragment_output(&mut self, varying: &V, pos: IVec2) -> FragmentOutput
+    {
+        self.fragment(varying, pos).into()
+    }
+
+    /// 开启ProgramPointSize后绘制点时调用，返回这个顶点的点大小(像素)
+    fn point_size(&mut self, _varying: &V) -> f32
+    {
+        1.
+    }
+
+    /// 绘制点时在sample和fragment之前调用，传入像素在点精灵内的坐标，
+    /// 范围是[0, 1]，原点在左上角
+    fn point_coord(&mut self, _coord: Vec2)
+    {
+
+    }
 }
\ No newline at end of file
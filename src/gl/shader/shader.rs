@@ -7,6 +7,13 @@ pub trait Shader<V: Varying> : Default
     fn reset(&mut self);
 
     fn get_varying(&self) -> &Vec<V>;
+    fn get_varying_mut(&mut self) -> &mut Vec<V>;
+
+    /// 给多线程顶点着色器阶段使用，除varying以外的字段都会被克隆
+    fn vertex_clone(&self) -> Self;
+
+    /// 相当于调用n次next，给多线程顶点着色器阶段跳到每一段的开头使用
+    fn next_n(&mut self, n: usize);
 
     fn compute_level(&mut self, sample_point: i32);
 }
\ No newline at end of file
@@ -1,4 +1,4 @@
-use std::{fs::File, io::BufReader, f32::consts::PI};
+use std::{fs::File, io::BufReader, f32::consts::PI, sync::OnceLock};
 
 use glam::{Mat4, Vec4, Vec3, Vec3A};
 use image::ImageFormat;
@@ -257,4 +257,93 @@ pub const fn find_log2_max16(num: i32) -> f32
     const LUT: [f32; 17] = [f32::NEG_INFINITY, 0., 1., 1.5849625, 2., 2.321928, 2.5849624, 2.807355,
     3., 3.169925, 3.321928, 3.4594316, 3.5849624, 3.7004397, 3.807355, 3.9068906, 4.];
     LUT[num as usize]
+}
+
+/// sRGB编码的8位值转换为[0, 1]内的线性值，使用查找表
+pub fn srgb_to_linear(value: u8) -> f32
+{
+    static LUT: OnceLock<[f32; 256]> = OnceLock::new();
+
+    LUT.get_or_init(|| std::array::from_fn(|i|
+    {
+        let c = i as f32 / 255.;
+        if c <= 0.04045 { c / 12.92 } else { ((c + 0.055) / 1.055).powf(2.4) }
+    }))[value as usize]
+}
+
+/// 线性值转换为sRGB编码的8位值，会先限制在[0, 1]内
+pub fn linear_to_srgb(value: f32) -> u8
+{
+    let c = value.clamp(0., 1.);
+    let c = if c <= 0.0031308 { c * 12.92 } else { 1.055 * c.powf(1. / 2.4) - 0.055 };
+
+    (c * 255. + 0.5) as u8
+}
+
+/// 把f32转换为5位指数、mantissa位尾数的小浮点数，不包含符号位，最近偶数舍入，超出范围时为无穷大
+fn encode_small_float(value: f32, mantissa: u32) -> u32
+{
+    let bits = value.to_bits() & 0x7FFFFFFF;
+
+    if value.is_nan()
+    {
+        return (0x1F << mantissa) | (1 << (mantissa - 1));
+    }
+
+    //舍去低shift位，最近偶数舍入，进位会自然地进到指数上
+    let round = |x: u32, shift: u32| (x + (1 << (shift - 1)) - 1 + ((x >> shift) & 1)) >> shift;
+    let exp = (bits >> 23) as i32 - 127 + 15;
+
+    if exp >= 0x1F
+    {
+        0x1F << mantissa
+    }
+    else if exp > 0
+    {
+        round(((exp as u32) << 23) | (bits & 0x7FFFFF), 23 - mantissa)
+    }
+    else
+    {
+        //非规格化数
+        let shift = (24 - mantissa) as i32 - exp;
+
+        if shift > 24 { 0 } else { round((bits & 0x7FFFFF) | 0x800000, shift as u32) }
+    }
+}
+
+fn decode_small_float(bits: u32, mantissa: u32) -> f32
+{
+    let exp = bits >> mantissa;
+    let m = bits & ((1 << mantissa) - 1);
+
+    match exp
+    {
+        0 => m as f32 * f32::powi(2., -14 - mantissa as i32),
+        0x1F => if m == 0 { f32::INFINITY } else { f32::NAN },
+        _ => f32::from_bits(((exp + 127 - 15) << 23) | (m << (23 - mantissa))),
+    }
+}
+
+/// f32转换为半精度浮点数
+pub fn f32_to_f16(value: f32) -> u16
+{
+    let sign = (value.to_bits() >> 16) & 0x8000;
+    (sign | encode_small_float(value, 10)) as u16
+}
+
+pub fn f16_to_f32(value: u16) -> f32
+{
+    let abs = decode_small_float(value as u32 & 0x7FFF, 10);
+    if value & 0x8000 != 0 { -abs } else { abs }
+}
+
+/// 打包为R11G11B10F格式，没有符号位，负数会变成0
+pub fn pack_r11g11b10f(r: f32, g: f32, b: f32) -> u32
+{
+    encode_small_float(r.max(0.), 6) | encode_small_float(g.max(0.), 6) << 11 | encode_small_float(b.max(0.), 5) << 22
+}
+
+pub fn unpack_r11g11b10f(value: u32) -> (f32, f32, f32)
+{
+    (decode_small_float(value & 0x7FF, 6), decode_small_float((value >> 11) & 0x7FF, 6), decode_small_float(value >> 22, 5))
 }
\ No newline at end of file
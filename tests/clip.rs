@@ -0,0 +1,92 @@
+mod common;
+
+use glam::Vec4;
+use hope::gl::enums::glBufferBit::GLBufferBit;
+use hope::gl::enums::glPrimitive::GLPrimitive;
+use hope::gl::glColor::GLColor;
+use hope::gl::glContext::GLContext;
+use hope::gl::glFrameBuffer::GLFrameBuffer;
+use hope::make_color;
+
+use common::{ColorShader, pixel};
+
+const SIZE: u32 = 32;
+
+/// 用白色画一个三角形，返回帧缓冲
+fn draw(positions: Vec<Vec4>) -> GLFrameBuffer
+{
+    let mut gl = GLContext::new(SIZE, SIZE);
+    let mut fb = gl.create_buffer();
+    let mut shader = ColorShader::new(positions, Vec4::ONE);
+
+    fb.attach_color();
+
+    gl.clear_color(make_color!(0));
+    gl.clear(GLBufferBit::Color, &mut fb);
+    gl.draw_arrays(&mut shader, GLPrimitive::Triangles, 3, 0, &mut fb);
+
+    fb
+}
+
+fn lit_count(fb: &GLFrameBuffer) -> usize
+{
+    (0..SIZE).flat_map(|y| (0..SIZE).map(move |x| (x, y))).filter(|&(x, y)| pixel(fb, SIZE, x, y)[0] != 0).count()
+}
+
+#[test]
+fn huge_triangle_outside_guard_band_covers_viewport()
+{
+    //三个顶点都远在保护带外侧，裁剪后应该盖住整个视口
+    let fb = draw(vec![
+        Vec4::new(-100., -100., 0., 1.),
+        Vec4::new(300., -100., 0., 1.),
+        Vec4::new(-100., 300., 0., 1.),
+    ]);
+
+    assert_eq!(lit_count(&fb), (SIZE * SIZE) as usize);
+}
+
+#[test]
+fn triangle_inside_guard_band_but_off_screen_draws_nothing()
+{
+    //在保护带以内但完全在视口右侧，不裁剪，只靠包围盒限制
+    let fb = draw(vec![
+        Vec4::new(2., -1., 0., 1.),
+        Vec4::new(6., -1., 0., 1.),
+        Vec4::new(2., 1., 0., 1.),
+    ]);
+
+    assert_eq!(lit_count(&fb), 0);
+}
+
+#[test]
+fn triangle_crossing_guard_band_keeps_on_screen_part()
+{
+    let fb = draw(vec![
+        Vec4::new(-1., -1., 0., 1.),
+        Vec4::new(1., -1., 0., 1.),
+        Vec4::new(-1., 0.5, 0., 1.),
+    ]);
+
+    //第2个顶点沿着边(1, -1)->(-1, 0.5)延长到保护带外，屏幕上覆盖的区域不变
+    let far = draw(vec![
+        Vec4::new(-1., -1., 0., 1.),
+        Vec4::new(1., -1., 0., 1.),
+        Vec4::new(-39., 29., 0., 1.),
+    ]);
+
+    assert!(lit_count(&fb) > 0);
+    assert_eq!(fb.get_color_buffer(), far.get_color_buffer());
+}
+
+#[test]
+fn triangle_behind_near_plane_draws_nothing()
+{
+    let fb = draw(vec![
+        Vec4::new(-1., -1., -2., 1.),
+        Vec4::new(1., -1., -2., 1.),
+        Vec4::new(0., 1., -2., 1.),
+    ]);
+
+    assert_eq!(lit_count(&fb), 0);
+}
\ No newline at end of file
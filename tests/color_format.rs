@@ -0,0 +1,79 @@
+use glam::{Vec2, Vec4};
+use hope::gl::enums::glColorFormat::GLColorFormat;
+use hope::gl::glFrameBuffer::GLFrameBuffer;
+
+/// 写入value后再读出来
+fn round_trip(format: GLColorFormat, value: Vec4) -> Vec4
+{
+    let mut fb = GLFrameBuffer::new(2, 2);
+
+    fb.attach_color_format(0, format);
+    fb.set_sample_color_f_at(0, 1, 1, 0, value);
+    fb.get_sample_color_f_at(0, 1, 1, 0)
+}
+
+fn assert_near(actual: Vec4, expected: Vec4, tolerance: f32)
+{
+    assert!((actual - expected).abs().max_element() <= tolerance, "{actual} != {expected}");
+}
+
+#[test]
+fn unorm_formats_clamp_and_quantize()
+{
+    let value = Vec4::new(1.5, 0.5, -1., 0.25);
+
+    assert_near(round_trip(GLColorFormat::R8, value), Vec4::new(1., 0., 0., 1.), 0.);
+    assert_near(round_trip(GLColorFormat::RG8, value), Vec4::new(1., 128. / 255., 0., 1.), 1e-6);
+    assert_near(round_trip(GLColorFormat::RGBA8, value), Vec4::new(1., 128. / 255., 0., 64. / 255.), 1e-6);
+}
+
+#[test]
+fn float_formats_keep_values_outside_unit_range()
+{
+    let value = Vec4::new(2.5, -1., 0.25, 16.);
+
+    assert_eq!(round_trip(GLColorFormat::RGBA32F, value), value);
+    assert_eq!(round_trip(GLColorFormat::RGBA16F, value), value);
+    assert_eq!(round_trip(GLColorFormat::R32F, value), Vec4::new(2.5, 0., 0., 1.));
+
+    //没有符号位，负数变为0，alpha总是1
+    assert_eq!(round_trip(GLColorFormat::R11G11B10F, value), Vec4::new(2.5, 0., 0.25, 1.));
+}
+
+#[test]
+fn packed_float_keeps_relative_precision()
+{
+    for v in [0.001, 0.3, 1., 7.77, 1000.]
+    {
+        let result = round_trip(GLColorFormat::R11G11B10F, Vec4::splat(v));
+
+        //红绿6位尾数，蓝色5位尾数
+        assert!((result.x - v).abs() <= v / 64., "{v} {}", result.x);
+        assert!((result.y - v).abs() <= v / 64., "{v} {}", result.y);
+        assert!((result.z - v).abs() <= v / 32., "{v} {}", result.z);
+    }
+}
+
+#[test]
+fn color_texture_keeps_format_and_values()
+{
+    let value = Vec4::new(3., 0.5, -2., 1.);
+
+    for format in [GLColorFormat::R8, GLColorFormat::RG8, GLColorFormat::RGBA8, GLColorFormat::RGBA16F, GLColorFormat::R32F, GLColorFormat::RGBA32F, GLColorFormat::R11G11B10F]
+    {
+        let mut fb = GLFrameBuffer::new(2, 2);
+
+        fb.attach_color_format(0, format);
+        fb.set_sample_color_f_at(0, 1, 0, 0, value);
+
+        let expected = fb.get_sample_color_f_at(0, 1, 0, 0);
+        let texture = fb.take_color_texture(0).unwrap();
+
+        assert!(!fb.is_color_attached(0));
+        assert_near(texture.get_texel(Vec2::new(1., 0.)), expected, 1e-6);
+
+        assert!(fb.attach_color_texture(0, texture));
+        assert_eq!(fb.get_color_format(0), format, "{format:?}");
+        assert_eq!(fb.get_sample_color_f_at(0, 1, 0, 0), expected, "{format:?}");
+    }
+}
\ No newline at end of file
@@ -0,0 +1,91 @@
+#![allow(dead_code)]
+
+use glam::{Vec4, IVec2, Vec2};
+use hope::gl::glColor::GLColor;
+use hope::gl::glFrameBuffer::GLFrameBuffer;
+use hope::gl::glTexture::GLTexture;
+use hope::gl::shader::fragment::FragmentOutput;
+use hope::gl::shader::program::Program;
+use hope::gl::shader::shader::Shader;
+use hope::gl::shader::varying::Varying;
+use shader::Shader;
+use varying::Varying;
+
+/// 顶点已经在裁剪空间中，每个顶点一个颜色
+#[derive(Default, Shader)]
+pub struct ColorShader
+{
+    #[varying(ColorVarying)]
+    pub varyings: Vec<ColorVarying>,
+
+    pub positions: Vec<Vec4>,
+    pub colors: Vec<Vec4>,
+
+    /// 丢弃所有片段，但是不设置DISCARD，用来测试没有声明时的行为
+    pub discard: bool,
+}
+
+impl ColorShader
+{
+    /// 所有顶点都是同一个颜色
+    pub fn new(positions: Vec<Vec4>, color: Vec4) -> Self
+    {
+        Self { colors: vec![color; positions.len()], positions, ..Default::default() }
+    }
+}
+
+impl Program<ColorVarying> for ColorShader
+{
+    fn vertex(&mut self, index: usize) -> Vec4
+    {
+        self.varyings.push(ColorVarying { color: self.colors[index] });
+        self.positions[index]
+    }
+
+    fn fragment(&mut self, varying: &ColorVarying, _: IVec2) -> GLColor
+    {
+        varying.color.into()
+    }
+
+    fn sample(&mut self, _: &ColorVarying)
+    {
+
+    }
+
+    fn fragment_output(&mut self, varying: &ColorVarying, pos: IVec2) -> FragmentOutput
+    {
+        if self.discard
+        {
+            FragmentOutput::discard()
+        }
+        else
+        {
+            self.fragment(varying, pos).into()
+        }
+    }
+}
+
+#[derive(Clone, Copy, Default, Varying)]
+pub struct ColorVarying
+{
+    pub color: Vec4,
+}
+
+/// 第0个颜色附着上(x, y)的RGBA8颜色
+pub fn pixel(fb: &GLFrameBuffer, width: u32, x: u32, y: u32) -> [u8; 4]
+{
+    let i = ((y * width + x) * 4) as usize;
+    fb.get_color_buffer()[i..i + 4].try_into().unwrap()
+}
+
+/// NDC坐标所在的像素，屏幕坐标的y轴向下
+pub fn ndc_to_pixel(size: u32, x: f32, y: f32) -> (i32, i32)
+{
+    (((x + 1.) * 0.5 * size as f32) as i32, ((1. - y) * 0.5 * size as f32) as i32)
+}
+
+/// 纹理上(x, y)的纹素，量化为8位
+pub fn texel(texture: &GLTexture, x: usize, y: usize) -> [u8; 4]
+{
+    (texture.get_texel(Vec2::new(x as f32, y as f32)) * 255.).round().to_array().map(|c| c as u8)
+}
\ No newline at end of file
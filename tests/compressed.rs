@@ -0,0 +1,212 @@
+use glam::{Vec2, Vec3, Vec4};
+use hope::gl::enums::glTextureFormat::GLTextureFormat;
+use hope::gl::glTexture::GLTexture;
+use hope::gl::util::srgb_to_linear;
+
+/// 解码一个4x4的块，按行排列
+fn decode(data: Vec<u8>, format: GLTextureFormat) -> Vec<Vec4>
+{
+    let texture = GLTexture::from_data(data, 4, 4, format).unwrap();
+
+    (0..16).map(|i| texture.get_texel(Vec2::new((i % 4) as f32, (i / 4) as f32))).collect()
+}
+
+/// 量化为8位
+fn decode8(data: Vec<u8>, format: GLTextureFormat) -> Vec<[u8; 4]>
+{
+    decode(data, format).into_iter().map(|c| (c * 255.).round().to_array().map(|c| c as u8)).collect()
+}
+
+/// 从低位开始依次写入各个字段
+struct BitWriter
+{
+    bits: u128,
+    pos: u32,
+}
+
+impl BitWriter
+{
+    fn put(&mut self, value: u128, n: u32)
+    {
+        self.bits |= (value & ((1 << n) - 1)) << self.pos;
+        self.pos += n;
+    }
+
+    /// 按照从高到低的顺序写入value的第high到第low位，BC6H有些模式的字段是反着存的
+    fn put_reversed(&mut self, value: u32, high: u32, low: u32)
+    {
+        for bit in (low..=high).rev()
+        {
+            self.put(((value >> bit) & 1) as u128, 1);
+        }
+    }
+}
+
+/// 每行的索引都是0 1 2 3的BC1颜色块，c0是红色，c1是蓝色
+fn bc1_block(c0: u16, c1: u16) -> Vec<u8>
+{
+    let indices: u32 = (0..16).map(|i| (i % 4) << (2 * i)).sum();
+
+    [c0.to_le_bytes(), c1.to_le_bytes()].concat().into_iter().chain(indices.to_le_bytes()).collect()
+}
+
+#[test]
+fn bc1_four_color_palette()
+{
+    let texels = decode8(bc1_block(0xF800, 0x001F), GLTextureFormat::BC1);
+
+    assert_eq!(texels[..4], [[255, 0, 0, 255], [0, 0, 255, 255], [170, 0, 85, 255], [85, 0, 170, 255]]);
+}
+
+#[test]
+fn bc1_three_color_palette_has_transparent_black()
+{
+    //c0 <= c1时第2个颜色是平均值，第3个是透明的黑色
+    let texels = decode8(bc1_block(0x001F, 0xF800), GLTextureFormat::BC1);
+
+    assert_eq!(texels[..4], [[0, 0, 255, 255], [255, 0, 0, 255], [128, 0, 128, 255], [0, 0, 0, 0]]);
+}
+
+#[test]
+fn bc4_eight_value_palette()
+{
+    let indices: u64 = (0..16).map(|i| (i % 8) << (3 * i)).sum();
+    let block = [200, 100].into_iter().chain(indices.to_le_bytes()[..6].iter().copied()).collect();
+    let texels = decode8(block, GLTextureFormat::BC4);
+
+    //a0 > a1时插值6个值：(6 * a0 + a1) / 7 ...
+    assert_eq!(texels[..8].iter().map(|c| c[0]).collect::<Vec<_>>(), [200, 100, 185, 171, 157, 142, 128, 114]);
+    assert!(texels.iter().all(|c| c[1] == 0 && c[2] == 0 && c[3] == 255));
+}
+
+#[test]
+fn bc7_mode6_interpolates_with_four_bit_weights()
+{
+    let mut w = BitWriter { bits: 0, pos: 0 };
+
+    w.put(1 << 6, 7);
+
+    //r从127到0，g从0到127，b是0，a是127，两个p位都是1
+    for (e0, e1) in [(127, 0), (0, 127), (0, 0), (127, 127)]
+    {
+        w.put(e0, 7);
+        w.put(e1, 7);
+    }
+
+    w.put(1, 1);
+    w.put(1, 1);
+
+    //第0个索引是锚点，只有3位
+    w.put(0, 3);
+
+    for i in 1..16
+    {
+        w.put(i, 4);
+    }
+
+    assert_eq!(w.pos, 128);
+
+    let texels = decode8(w.bits.to_le_bytes().to_vec(), GLTextureFormat::BC7);
+
+    assert_eq!(texels[0], [255, 1, 1, 255]);
+    assert_eq!(texels[1], [239, 17, 1, 255]);
+    assert_eq!(texels[15], [1, 255, 1, 255]);
+}
+
+#[test]
+fn etc2_individual_mode()
+{
+    //左半块基色R=15，右半块是0，码表0的修正值是{2, 8, -2, -8}，第1和第3列的索引是3
+    let mut low = 0u32;
+
+    for y in 0..4
+    {
+        for x in [1, 3]
+        {
+            low |= (1 << (x * 4 + y)) | (1 << (16 + x * 4 + y));
+        }
+    }
+
+    let block = [0xF0, 0, 0, 0].into_iter().chain(low.to_be_bytes()).collect();
+    let texels = decode8(block, GLTextureFormat::ETC2RGB8);
+
+    assert_eq!(texels[..4], [[255, 2, 2, 255], [247, 0, 0, 255], [2, 2, 2, 255], [0, 0, 0, 255]]);
+}
+
+#[test]
+fn etc2_planar_mode()
+{
+    //差分模式下B溢出时是平面模式，O、H、V都取最大值
+    let high: u32 = (63 << 25) | (1 << 24) | (63 << 17) | (1 << 16) | (7 << 13) | (3 << 11) | (7 << 7) | (31 << 2) | 3;
+    let low: u32 = (127 << 25) | (63 << 19) | (63 << 13) | (127 << 6) | 63;
+    let block = high.to_be_bytes().into_iter().chain(low.to_be_bytes()).collect();
+
+    assert!(decode8(block, GLTextureFormat::ETC2RGB8).iter().all(|c| *c == [255; 4]));
+}
+
+#[test]
+fn eac_alpha()
+{
+    //基础值128，乘数1，码表13的第7个修正值是9
+    let mut alpha: u64 = (128 << 56) | (1 << 52) | (13 << 48);
+
+    for i in 0..16
+    {
+        alpha |= 7 << (45 - 3 * i);
+    }
+
+    let block = alpha.to_be_bytes().into_iter().chain([0xF0, 0, 0, 0, 0, 0, 0, 0]).collect();
+    let texels = decode8(block, GLTextureFormat::ETC2RGBA8);
+
+    assert_eq!(texels[0], [255, 2, 2, 137]);
+    assert!(texels.iter().all(|c| c[3] == 137));
+}
+
+#[test]
+fn bc6h_mode14_constant_one()
+{
+    //16位的端点31711经过最后的缩放是半精度的1.0
+    let endpoint = 31711;
+    let mut w = BitWriter { bits: 0, pos: 0 };
+
+    w.put(15, 5);
+
+    for _ in 0..3
+    {
+        w.put(endpoint as u128, 10);
+    }
+
+    for _ in 0..3
+    {
+        w.put(0, 4);
+        w.put_reversed(endpoint, 15, 10);
+    }
+
+    assert!(decode(w.bits.to_le_bytes().to_vec(), GLTextureFormat::BC6HUF16).iter().all(|c| *c == Vec4::ONE));
+}
+
+#[test]
+fn bc6h_reserved_mode_is_black()
+{
+    assert!(decode(19u128.to_le_bytes().to_vec(), GLTextureFormat::BC6HUF16).iter().all(|c| c.truncate() == Vec3::ZERO));
+}
+
+#[test]
+fn srgb_variants_decode_to_linear()
+{
+    let bc1 = bc1_block(0xF800, 0x001F);
+    let bc3 = [200, 100, 0x24, 0x92, 0x49, 0x24, 0x92, 0x49].into_iter().chain(bc1.iter().copied()).collect();
+    let bc7 = (0..16u32).map(|i| (i * 29 + 64) as u8).collect();
+
+    for (data, linear, srgb) in [(bc1, GLTextureFormat::BC1, GLTextureFormat::BC1SRGB), (bc3, GLTextureFormat::BC3, GLTextureFormat::BC3SRGB),
+        (bc7, GLTextureFormat::BC7, GLTextureFormat::BC7SRGB)]
+    {
+        //alpha不做转换
+        let expected = decode8(data.clone(), linear).into_iter().map(|[r, g, b, a]| Vec4::new(srgb_to_linear(r), srgb_to_linear(g), srgb_to_linear(b), a as f32 / 255.));
+
+        for (actual, expected) in decode(data, srgb).into_iter().zip(expected)
+        {
+            assert!((actual - expected).abs().max_element() < 1e-6, "{srgb:?} {actual} != {expected}");
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,116 @@
+mod common;
+
+use glam::{Vec4, Vec3, Vec2};
+use hope::gl::enums::glBufferBit::GLBufferBit;
+use hope::gl::enums::glDepthFormat::GLDepthFormat;
+use hope::gl::enums::glFunction::GLFunction;
+use hope::gl::enums::glPrimitive::GLPrimitive;
+use hope::gl::glColor::GLColor;
+use hope::gl::glContext::GLContext;
+use hope::gl::glFrameBuffer::GLFrameBuffer;
+use hope::gl::util::perspective_projection;
+use hope::make_color;
+
+use common::{ColorShader, pixel};
+
+const SIZE: u32 = 64;
+
+/// 先画远处的蓝色三角形，再画近处的红色三角形，返回中心像素的颜色
+fn draw_overlapping(format: GLDepthFormat) -> [u8; 4]
+{
+    let mut gl = GLContext::new(SIZE, SIZE);
+    let mut fb = gl.create_buffer();
+    let mut shader = ColorShader::default();
+
+    fb.attach_color();
+    fb.attach_depth_format(format);
+
+    //两个三角形离相机都很近，NDC的z都小于0
+    let proj = perspective_projection(60f32.to_radians(), 1., 1., 100.);
+
+    for (z, color) in [(-1.5, Vec4::new(0., 0., 1., 1.)), (-1.2, Vec4::new(1., 0., 0., 1.))]
+    {
+        for pos in [Vec3::new(-1., -1., z), Vec3::new(1., -1., z), Vec3::new(0., 1., z)]
+        {
+            shader.positions.push(proj * Vec4::from((pos, 1.)));
+            shader.colors.push(color);
+        }
+    }
+
+    gl.enable(GLFunction::DepthTest);
+    gl.clear_color(make_color!(0));
+    gl.clear_depth(1.);
+    gl.clear(GLBufferBit::Color | GLBufferBit::Depth, &mut fb);
+    gl.draw_arrays(&mut shader, GLPrimitive::Triangles, 6, 0, &mut fb);
+
+    pixel(&fb, SIZE, SIZE / 2, SIZE / 2)
+}
+
+#[test]
+fn nearer_triangle_wins_in_fixed_point_depth()
+{
+    for format in [GLDepthFormat::D16, GLDepthFormat::D24, GLDepthFormat::D24S8, GLDepthFormat::D32F]
+    {
+        assert_eq!(draw_overlapping(format), [255, 0, 0, 255], "{format:?}");
+    }
+}
+
+#[test]
+fn depth_formats_quantize_and_clamp()
+{
+    for (format, step) in [(GLDepthFormat::D16, 1. / 65535.), (GLDepthFormat::D24, 1. / 16777215.), (GLDepthFormat::D24S8, 1. / 16777215.)]
+    {
+        let mut fb = GLFrameBuffer::new(2, 2);
+
+        fb.attach_depth_format(format);
+
+        for depth in [0., 0.3, 0.5, 0.999, 1.]
+        {
+            fb.set_depth(1, 1, depth);
+            assert!((fb.get_depth(1, 1) - depth).abs() <= step * 0.5 + f32::EPSILON, "{format:?} {depth}");
+        }
+
+        fb.set_depth(1, 1, 1.5);
+        assert_eq!(fb.get_depth(1, 1), 1., "{format:?}");
+
+        fb.set_depth(1, 1, -0.5);
+        assert_eq!(fb.get_depth(1, 1), 0., "{format:?}");
+    }
+
+    let mut fb = GLFrameBuffer::new(2, 2);
+
+    fb.attach_depth_format(GLDepthFormat::D32F);
+    fb.set_depth(1, 1, 0.3);
+    assert_eq!(fb.get_depth(1, 1), 0.3);
+}
+
+#[test]
+fn packed_stencil_survives_depth_writes()
+{
+    let mut fb = GLFrameBuffer::new(2, 2);
+
+    fb.attach_depth_format(GLDepthFormat::D24S8);
+    fb.set_stencil(0, 1, 0xA5);
+    fb.set_depth(0, 1, 0.25);
+
+    assert_eq!(fb.get_stencil(0, 1), 0xA5);
+
+    fb.set_stencil(0, 1, 0x3C);
+
+    assert!((fb.get_depth(0, 1) - 0.25).abs() < 1e-6);
+    assert_eq!(fb.get_stencil(0, 1), 0x3C);
+}
+
+#[test]
+fn depth_texture_reads_back_quantized_depth()
+{
+    let mut fb = GLFrameBuffer::new(2, 2);
+
+    fb.attach_depth_format(GLDepthFormat::D16);
+    fb.set_depth(1, 0, 0.3);
+
+    let texture = fb.get_depth_texture().unwrap();
+
+    assert_eq!(texture.get_texel(Vec2::new(1., 0.)).x, fb.get_depth(1, 0));
+    assert_eq!(texture.get_texel(Vec2::new(0., 0.)).x, 1.);
+}
\ No newline at end of file
@@ -0,0 +1,31 @@
+mod common;
+
+use glam::Vec4;
+use hope::gl::enums::glBufferBit::GLBufferBit;
+use hope::gl::enums::glPrimitive::GLPrimitive;
+use hope::gl::glContext::GLContext;
+
+use common::ColorShader;
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "Program::DISCARD")]
+fn discard_without_const_panics_in_debug()
+{
+    let mut gl = GLContext::new(16, 16);
+    let mut fb = gl.create_buffer();
+
+    fb.attach_color();
+
+    let mut shader = ColorShader::new(vec![
+        Vec4::new(-1., -1., 0., 1.),
+        Vec4::new(1., -1., 0., 1.),
+        Vec4::new(0., 1., 0., 1.),
+    ], Vec4::ONE);
+
+    //故意不设置DISCARD
+    shader.discard = true;
+
+    gl.clear(GLBufferBit::Color, &mut fb);
+    gl.draw_arrays(&mut shader, GLPrimitive::Triangles, 3, 0, &mut fb);
+}
\ No newline at end of file
@@ -0,0 +1,36 @@
+mod common;
+
+use hope::gl::glColor::GLColor;
+use hope::gl::glTexture::GLTexture;
+use hope::make_color;
+
+use common::texel;
+
+#[test]
+fn box_mipmap_rounds_to_nearest()
+{
+    //1.75四舍五入是2，截断是1
+    let colors = vec![make_color!(1), make_color!(2), make_color!(2), make_color!(2)];
+    let mut texture = GLTexture::from_colors(colors, 2, 2).unwrap();
+
+    assert!(texture.create_mipmap(None));
+    assert_eq!(texel(texture.get_mipmap(1.), 0, 0), [2, 2, 2, 2]);
+}
+
+#[test]
+fn box_mipmap_odd_size_keeps_constant_color()
+{
+    //奇数长度的总权重是长乘宽，4101x4101时255乘总权重超过u32
+    let size = 4101;
+    let mut texture = GLTexture::from_colors(vec![make_color!(255); size * size], size, size).unwrap();
+
+    assert!(texture.create_mipmap(Some(1)));
+
+    let mip = texture.get_mipmap(1.);
+    assert_eq!((mip.get_width(), mip.get_height()), (size / 2, size / 2));
+
+    for (x, y) in [(0, 0), (size / 4, size / 4), (size / 2 - 1, size / 2 - 1)]
+    {
+        assert_eq!(texel(mip, x, y), [255; 4]);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,109 @@
+mod common;
+
+use glam::{Vec4, IVec4};
+use hope::gl::enums::glBufferBit::GLBufferBit;
+use hope::gl::enums::glFunction::GLFunction;
+use hope::gl::enums::glPrimitive::GLPrimitive;
+use hope::gl::enums::glSamplePoint::GLSamplePoint;
+use hope::gl::glColor::GLColor;
+use hope::gl::glContext::GLContext;
+use hope::gl::glFrameBuffer::GLFrameBuffer;
+use hope::gl::sampler::GLFilterFunc;
+use hope::make_color;
+
+use common::{ColorShader, pixel};
+
+const SIZE: u32 = 16;
+
+/// 用4倍多重采样画左下半边的白色三角形，深度是0.5
+fn draw_multisample(gl: &mut GLContext) -> GLFrameBuffer
+{
+    let mut fb = gl.create_multisample_buffer(GLSamplePoint::X4);
+    let mut shader = ColorShader::new(vec![
+        Vec4::new(-1., -1., 0., 1.),
+        Vec4::new(1., -1., 0., 1.),
+        Vec4::new(-1., 1., 0., 1.),
+    ], Vec4::ONE);
+
+    fb.attach_color();
+    fb.attach_depth();
+
+    gl.enable(GLFunction::DepthTest);
+    gl.clear_color(make_color!(0));
+    gl.clear_depth(1.);
+    gl.clear(GLBufferBit::Color | GLBufferBit::Depth, &mut fb);
+    gl.draw_arrays(&mut shader, GLPrimitive::Triangles, 3, 0, &mut fb);
+
+    fb
+}
+
+#[test]
+fn resolve_averages_samples()
+{
+    let mut gl = GLContext::new(SIZE, SIZE);
+    let src = draw_multisample(&mut gl);
+    let mut dst = gl.create_buffer();
+
+    dst.attach_color();
+    dst.attach_depth();
+    src.resolve(&mut dst);
+
+    //三角形内部和外部
+    assert_eq!(pixel(&dst, SIZE, 1, SIZE - 2), [255, 255, 255, 255]);
+    assert_eq!(pixel(&dst, SIZE, SIZE - 2, 1), [0, 0, 0, 0]);
+
+    let mut partial = 0;
+
+    for y in 0..SIZE as i32
+    {
+        for x in 0..SIZE as i32
+        {
+            let average = (0..4).map(|s| src.get_sample_color(x, y, s).r as u32).sum::<u32>() as f32 / 4.;
+            let resolved = pixel(&dst, SIZE, x as u32, y as u32)[0];
+
+            assert!((resolved as f32 - average).abs() <= 1., "({x}, {y}): {resolved} {average}");
+            assert_eq!(dst.get_depth(x, y), src.get_sample_depth(x, y, 0));
+
+            if resolved != 0 && resolved != 255
+            {
+                partial += 1;
+            }
+        }
+    }
+
+    //对角线上的像素只有部分采样点被覆盖
+    assert!(partial > 0);
+}
+
+#[test]
+fn blit_from_multisample_matches_resolve()
+{
+    let mut gl = GLContext::new(SIZE, SIZE);
+    let src = draw_multisample(&mut gl);
+    let mut resolved = gl.create_buffer();
+    let mut blitted = gl.create_buffer();
+    let rect = IVec4::new(0, 0, SIZE as i32, SIZE as i32);
+
+    resolved.attach_color();
+    blitted.attach_color();
+    src.resolve(&mut resolved);
+    gl.blit_framebuffer(&src, rect, &mut blitted, rect, GLBufferBit::Color, GLFilterFunc::Nearest);
+
+    assert_eq!(blitted.get_color_buffer(), resolved.get_color_buffer());
+}
+
+#[test]
+fn resolve_rejects_mismatched_target()
+{
+    let mut gl = GLContext::new(SIZE, SIZE);
+    let src = draw_multisample(&mut gl);
+    let mut dst = GLFrameBuffer::new(SIZE as usize / 2, SIZE as usize / 2);
+
+    dst.attach_color();
+
+    let before = dst.get_color_buffer().to_vec();
+
+    src.resolve(&mut dst);
+
+    assert_eq!(dst.get_color_buffer(), before);
+}
\ No newline at end of file
@@ -0,0 +1,50 @@
+mod common;
+
+use glam::Vec4;
+use hope::gl::enums::glBufferBit::GLBufferBit;
+use hope::gl::enums::glPolygonMode::GLPolygonMode;
+use hope::gl::enums::glPrimitive::GLPrimitive;
+use hope::gl::glColor::GLColor;
+use hope::gl::glContext::GLContext;
+use hope::gl::glFrameBuffer::GLFrameBuffer;
+use hope::make_color;
+
+use common::{ColorShader, ndc_to_pixel, pixel};
+
+const SIZE: u32 = 100;
+
+/// NDC坐标附近3x3像素内是否有被画到的像素
+fn lit_near(fb: &GLFrameBuffer, x: f32, y: f32) -> bool
+{
+    let (cx, cy) = ndc_to_pixel(SIZE, x, y);
+
+    (cx - 1..=cx + 1).any(|px| (cy - 1..=cy + 1).any(|py| pixel(fb, SIZE, px as u32, py as u32)[0] != 0))
+}
+
+#[test]
+fn line_mode_near_clipped_triangle_has_no_diagonal()
+{
+    let mut gl = GLContext::new(SIZE, SIZE);
+    let mut fb = gl.create_buffer();
+
+    fb.attach_color();
+
+    //第0个顶点在近平面外侧，裁剪后是四边形[P, v1, v2, Q]，三角形扇的对角线是P到v2
+    let mut shader = ColorShader::new(vec![
+        Vec4::new(-0.8, -0.8, -3., 1.),
+        Vec4::new(0.8, -0.8, 0., 1.),
+        Vec4::new(0., 0.8, 0., 1.),
+    ], Vec4::ONE);
+
+    gl.polygon_mode(GLPolygonMode::Line);
+    gl.clear_color(make_color!(0));
+    gl.clear(GLBufferBit::Color, &mut fb);
+    gl.draw_arrays(&mut shader, GLPrimitive::Triangles, 3, 0, &mut fb);
+
+    //原来的边v1-v2和裁剪出来的边Q-P
+    assert!(lit_near(&fb, 0.4, 0.));
+    assert!(lit_near(&fb, 0., -0.267));
+
+    //P(0.267, -0.8)到v2(0, 0.8)的中点
+    assert!(!lit_near(&fb, 0.133, 0.));
+}
\ No newline at end of file
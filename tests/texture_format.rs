@@ -0,0 +1,67 @@
+use glam::{Vec2, Vec4};
+use hope::gl::enums::glTextureFormat::GLTextureFormat;
+use hope::gl::glTexture::GLTexture;
+
+/// 把value编码为format格式的1x1纹理再读出来
+fn round_trip(format: GLTextureFormat, value: Vec4) -> Vec4
+{
+    GLTexture::from_vec4(&[value], 1, 1, format).unwrap().get_texel(Vec2::ZERO)
+}
+
+fn assert_near(actual: Vec4, expected: Vec4, tolerance: f32)
+{
+    assert!((actual - expected).abs().max_element() <= tolerance, "{actual} != {expected}");
+}
+
+#[test]
+fn unorm_formats_fill_missing_channels()
+{
+    let value = Vec4::new(0.2, 0.4, 0.6, 0.8);
+
+    assert_near(round_trip(GLTextureFormat::R8, value), Vec4::new(0.2, 0., 0., 1.), 0.5 / 255.);
+    assert_near(round_trip(GLTextureFormat::RG8, value), Vec4::new(0.2, 0.4, 0., 1.), 0.5 / 255.);
+    assert_near(round_trip(GLTextureFormat::RGB8, value), Vec4::new(0.2, 0.4, 0.6, 1.), 0.5 / 255.);
+    assert_near(round_trip(GLTextureFormat::RGBA8, value), value, 0.5 / 255.);
+    assert_eq!(round_trip(GLTextureFormat::RGBA8, Vec4::new(2., -1., 0., 1.)), Vec4::new(1., 0., 0., 1.));
+}
+
+#[test]
+fn float_formats_round_trip()
+{
+    let value = Vec4::new(12.5, -0.75, 0.125, 2.);
+
+    assert_eq!(round_trip(GLTextureFormat::RGBA32F, value), value);
+    assert_eq!(round_trip(GLTextureFormat::RGBA16F, value), value);
+    assert_eq!(round_trip(GLTextureFormat::R32F, value), Vec4::new(12.5, 0., 0., 1.));
+    assert_eq!(round_trip(GLTextureFormat::R11G11B10F, value), Vec4::new(12.5, 0., 0.125, 1.));
+
+    //超出半精度范围的值变为无穷大
+    assert_eq!(round_trip(GLTextureFormat::RGBA16F, Vec4::splat(1e6)).x, f32::INFINITY);
+}
+
+#[test]
+fn depth_formats_round_trip()
+{
+    assert_near(round_trip(GLTextureFormat::D16, Vec4::splat(0.3)), Vec4::new(0.3, 0., 0., 1.), 0.5 / 65535.);
+    assert_eq!(round_trip(GLTextureFormat::D16, Vec4::splat(2.)).x, 1.);
+    assert_eq!(round_trip(GLTextureFormat::D32F, Vec4::splat(0.3)), Vec4::new(0.3, 0., 0., 1.));
+}
+
+#[test]
+fn integer_formats_keep_raw_values()
+{
+    assert_eq!(round_trip(GLTextureFormat::R32UI, Vec4::new(70000., 5., 5., 5.)), Vec4::new(70000., 0., 0., 1.));
+    assert_eq!(round_trip(GLTextureFormat::RGBA8UI, Vec4::new(1., 2., 200., 255.)), Vec4::new(1., 2., 200., 255.));
+
+    let texture = GLTexture::from_data(70000u32.to_ne_bytes().to_vec(), 1, 1, GLTextureFormat::R32UI).unwrap();
+
+    assert_eq!(texture.get_texel(Vec2::ZERO).x, 70000.);
+}
+
+#[test]
+fn from_data_checks_length()
+{
+    assert!(GLTexture::from_data(vec![0; 7], 1, 1, GLTextureFormat::RGBA16F).is_none());
+    assert!(GLTexture::from_data(vec![0; 8], 1, 1, GLTextureFormat::RGBA16F).is_some());
+    assert!(GLTexture::from_vec4(&[Vec4::ZERO; 3], 2, 2, GLTextureFormat::RGBA32F).is_none());
+}
\ No newline at end of file